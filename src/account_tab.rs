@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph, Row, Table, TableState},
+};
+
+use crate::account_watcher::{self, Account, Limits, Scope};
+use crate::app::{AppMessage, Focus, Job, border_style};
+use crate::job_state::JobState;
+use crate::poll_watcher::PollWatcherHandle;
+use crate::scroll;
+use crate::slurm;
+
+/// Fairshare and association and QOS limits of the user, refreshed while the tab is shown.
+pub struct AccountTab {
+    /// `None` until the first lookup finished.
+    account: Option<Result<Account, String>>,
+    shares_state: TableState,
+    table_state: TableState,
+    /// Rows of the limits table, as last rendered.
+    table_height: u16,
+    watcher: Option<PollWatcherHandle>,
+}
+
+impl AccountTab {
+    pub fn new() -> Self {
+        Self {
+            account: None,
+            shares_state: TableState::default(),
+            table_state: TableState::default(),
+            table_height: 0,
+            watcher: None,
+        }
+    }
+
+    pub fn activate(&mut self, app: Sender<AppMessage>, interval: Duration) {
+        self.watcher = Some(PollWatcherHandle::new(app, interval, || {
+            AppMessage::Account(account_watcher::account())
+        }));
+    }
+
+    pub fn deactivate(&mut self) {
+        self.watcher = None;
+    }
+
+    pub fn set_account(&mut self, account: Result<Account, String>) {
+        self.account = Some(account);
+        if self.table_state.selected().is_none() {
+            self.shares_state.select_first();
+            self.table_state.select_first();
+        }
+    }
+
+    pub fn help() -> Vec<(&'static str, &'static str)> {
+        vec![("⏴/⏵", "panel"), ("⏶/⏷", "navigate"), ("r", "refresh")]
+    }
+
+    /// Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent, focus: Focus) -> bool {
+        if key.code == KeyCode::Char('r') {
+            if let Some(watcher) = &self.watcher {
+                watcher.refresh();
+            }
+            return true;
+        }
+        match focus {
+            // the shares table is sized to fit
+            Focus::Shares => scroll::select_row(&mut self.shares_state, key, 1),
+            _ => scroll::select_row(&mut self.table_state, key, self.table_height),
+        }
+    }
+
+    /// The usage of the limits is computed from `jobs`, the current job snapshot, unless Slurm
+    /// tells the usage of a group limit.
+    /// `focus` is `None` while a dialog is open.
+    pub fn render(&mut self, f: &mut Frame, area: Rect, focus: Option<Focus>, jobs: &[&Job]) {
+        let block = |title: String, panel: Focus| {
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(border_style(focus, panel))
+        };
+        // a single panel stands for both until the account is loaded
+        let any_panel = focus.unwrap_or(Focus::Shares);
+        let account = match &self.account {
+            Some(Ok(account)) => account,
+            Some(Err(e)) => {
+                let error =
+                    Paragraph::new(Line::styled(e.as_str(), Style::default().fg(Color::Red)))
+                        .block(block("─Account".to_owned(), any_panel));
+                f.render_widget(error, area);
+                return;
+            }
+            None => {
+                let loading = Paragraph::new(Line::styled(
+                    "Loading…",
+                    Style::default().add_modifier(Modifier::DIM),
+                ))
+                .block(block("─Account".to_owned(), any_panel));
+                f.render_widget(loading, area);
+                return;
+            }
+        };
+        let shares_limits = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(account.shares.len() as u16 + 3),
+                    Constraint::Min(3),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+
+        let shares = Table::new(
+            account.shares.iter().map(|s| {
+                Row::new(vec![
+                    Line::styled(s.account.as_str(), Style::default().fg(Color::Blue)),
+                    Line::styled(s.user.as_str(), Style::default().fg(Color::Green)),
+                    Line::raw(s.raw_shares.as_str()).right_aligned(),
+                    Line::raw(s.norm_shares.as_str()).right_aligned(),
+                    Line::raw(s.raw_usage.as_str()).right_aligned(),
+                    Line::raw(s.effective_usage.as_str()).right_aligned(),
+                    Line::raw(s.fairshare.as_str()).right_aligned(),
+                ])
+            }),
+            [
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Length(10),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                Line::raw("Account"),
+                Line::raw("User"),
+                Line::raw("RawShares").right_aligned(),
+                Line::raw("NormShares").right_aligned(),
+                Line::raw("RawUsage").right_aligned(),
+                Line::raw("EffUsage").right_aligned(),
+                Line::raw("FairShare").right_aligned(),
+            ])
+            .style(bold),
+        )
+        .row_highlight_style(if focus == Some(Focus::Shares) {
+            Style::default().bg(Color::Green).fg(Color::Black)
+        } else {
+            Style::default()
+        })
+        .block(block(
+            format!("─Fairshare of {}", account.user),
+            Focus::Shares,
+        ));
+        f.render_stateful_widget(shares, shares_limits[0], &mut self.shares_state);
+
+        let mut rows = Vec::new();
+        for limits in &account.limits {
+            let scope = match (limits.scope, limits.partition.as_str()) {
+                (Scope::Assoc, "") => format!("Account {}", limits.name),
+                (Scope::Assoc, partition) => format!("Account {} ({})", limits.name, partition),
+                (Scope::Qos, _) => format!("QOS {}", limits.name),
+            };
+            let scoped: Vec<&Job> = jobs
+                .iter()
+                .copied()
+                .filter(|j| applies(limits, j))
+                .collect();
+            let own: Vec<&Job> = scoped
+                .iter()
+                .copied()
+                .filter(|j| j.user == account.user)
+                .collect();
+            for (key, value) in &limits.limits {
+                // a row per TRES of a TRES limit
+                let values: Vec<(&str, Option<&str>)> = if key.contains("TRES") {
+                    value
+                        .split(',')
+                        .map(|item| (item, item.split_once('=').map(|(tres, _)| tres)))
+                        .collect()
+                } else {
+                    vec![(value.as_str(), None)]
+                };
+                for (value, tres) in values {
+                    rows.push(Row::new(vec![
+                        Line::styled(scope.clone(), Style::default().fg(Color::Blue)),
+                        Line::styled(*key, Style::default().fg(Color::Yellow)),
+                        Line::raw(value.to_owned()),
+                        Line::raw(if key.starts_with("Grp") {
+                            group_usage(limits, key, tres)
+                                .or_else(|| usage(key, tres, &scoped))
+                                .unwrap_or_default()
+                        } else {
+                            usage(key, tres, &own).unwrap_or_default()
+                        }),
+                    ]));
+                }
+            }
+        }
+        let title = format!("─Limits ({})", rows.len());
+        let limits = Table::new(
+            rows,
+            [
+                Constraint::Length(24),
+                Constraint::Length(12),
+                Constraint::Length(20),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(vec!["Scope", "Limit", "Value", "In use"]).style(bold))
+        .row_highlight_style(Style::default().bg(Color::Green).fg(Color::Black))
+        .block(block(title, Focus::Limits));
+        f.render_stateful_widget(limits, shares_limits[1], &mut self.table_state);
+        // borders and header
+        self.table_height = shares_limits[1].height.saturating_sub(3);
+    }
+}
+
+/// Whether the job counts towards the limits.
+fn applies(limits: &Limits, job: &Job) -> bool {
+    match limits.scope {
+        Scope::Assoc => {
+            job.account == limits.name
+                && (limits.partition.is_empty() || job.partition == limits.partition)
+        }
+        Scope::Qos => job.qos == limits.name,
+    }
+}
+
+/// The usage of a group limit by all jobs, from `scontrol show assoc_mgr`.
+fn group_usage(limits: &Limits, key: &str, tres: Option<&str>) -> Option<String> {
+    let value = limits.usage.get(match key {
+        "GrpSubmit" => "GrpSubmitJobs",
+        key => key,
+    })?;
+    let value = match tres {
+        Some(tres) => value
+            .split(',')
+            .find_map(|t| t.strip_prefix(tres)?.strip_prefix('='))?,
+        None => value,
+    };
+    let (_, usage) = value.split_once('(')?;
+    let usage = usage.trim_end_matches(')');
+    Some(match (key, tres) {
+        ("GrpJobs", _) => format!("{} running", usage),
+        ("GrpSubmit", _) => format!("{} queued", usage),
+        // in megabytes
+        (_, Some("mem")) => slurm::format_size(usage.parse::<u64>().ok()? * 1024 * 1024),
+        _ => usage.to_owned(),
+    })
+}
+
+/// What the jobs use of a limit, `None` where the job list does not tell (e.g. `MaxWall`).
+/// `tres` is the TRES of a TRES limit.
+fn usage(key: &str, tres: Option<&str>, jobs: &[&Job]) -> Option<String> {
+    let running: Vec<&Job> = jobs
+        .iter()
+        .copied()
+        .filter(|j| j.state == JobState::Running)
+        .collect();
+    if key.contains("Submit") {
+        return Some(format!("{} queued", jobs.len()));
+    }
+    if key.contains("Jobs") {
+        return Some(format!("{} running", running.len()));
+    }
+    let tres = tres?;
+    let amounts = running.iter().map(|j| j.tres.amount(tres));
+    // a per job limit is about the largest job
+    let amount = if key == "MaxTRES" {
+        amounts.fold(0.0, f64::max)
+    } else {
+        amounts.fold(0.0, |sum, a| sum + a)
+    };
+    Some(if tres == "mem" {
+        slurm::format_size(amount as u64)
+    } else {
+        format!("{}", amount.round())
+    })
+}
@@ -0,0 +1,226 @@
+use std::{collections::HashMap, process::Command};
+
+use crate::slurm;
+
+/// Fairshare and limits of the user running turm.
+pub struct Account {
+    pub user: String,
+    pub shares: Vec<Share>,
+    pub limits: Vec<Limits>,
+}
+
+/// A line of `sshare`, of an account if `user` is empty.
+pub struct Share {
+    pub account: String,
+    pub user: String,
+    pub raw_shares: String,
+    pub norm_shares: String,
+    pub raw_usage: String,
+    pub effective_usage: String,
+    pub fairshare: String,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scope {
+    Assoc,
+    Qos,
+}
+
+/// The limits of an association or QOS that are set, from `sacctmgr`.
+pub struct Limits {
+    pub scope: Scope,
+    /// The account of an association, or the name of a QOS.
+    pub name: String,
+    /// The partition of an association, empty for all partitions.
+    pub partition: String,
+    /// E.g. `("GrpTRES", "cpu=100,gres/gpu=8")`.
+    pub limits: Vec<(&'static str, String)>,
+    /// The fields of the association or QOS in `scontrol show assoc_mgr`, where limits read
+    /// `limit(usage)`, e.g. `GrpTRES` is `cpu=100(48),mem=N(0)`. Empty if it is not listed.
+    pub usage: HashMap<String, String>,
+}
+
+const SHARE_FIELDS: [&str; 7] = [
+    "Account",
+    "User",
+    "RawShares",
+    "NormShares",
+    "RawUsage",
+    "EffectvUsage",
+    "FairShare",
+];
+
+/// Account, partition and QOS, followed by the limits.
+const ASSOC_FIELDS: [&str; 10] = [
+    "Account",
+    "Partition",
+    "QOS",
+    "GrpTRES",
+    "GrpJobs",
+    "GrpSubmit",
+    "MaxJobs",
+    "MaxSubmit",
+    "MaxTRES",
+    "MaxWall",
+];
+
+/// The name, followed by the limits.
+const QOS_FIELDS: [&str; 9] = [
+    "Name",
+    "GrpTRES",
+    "GrpJobs",
+    "GrpSubmit",
+    "MaxJobsPU",
+    "MaxSubmitPU",
+    "MaxTRESPU",
+    "MaxTRES",
+    "MaxWall",
+];
+
+/// The fairshare and limits of the user running turm.
+pub fn account() -> Result<Account, String> {
+    let user = slurm::current_user();
+    let user = user.as_str();
+    let shares = slurm::output(Command::new("sshare").args([
+        "--noheader",
+        "--parsable2",
+        "--users",
+        user,
+        "--format",
+        &SHARE_FIELDS.join(","),
+    ]))?;
+    let shares = parse_rows(&shares, SHARE_FIELDS.len())
+        .map(|r| Share {
+            account: r[0].to_owned(),
+            user: r[1].to_owned(),
+            raw_shares: r[2].to_owned(),
+            norm_shares: r[3].to_owned(),
+            raw_usage: r[4].to_owned(),
+            effective_usage: r[5].to_owned(),
+            fairshare: r[6].to_owned(),
+        })
+        .collect();
+
+    let assocs = slurm::output(Command::new("sacctmgr").args([
+        "--noheader",
+        "--parsable2",
+        "show",
+        "assoc",
+        "where",
+        &format!("user={}", user),
+        &format!("format={}", ASSOC_FIELDS.join(",")),
+    ]))?;
+    // the usage of group limits counts the jobs of all users, which squeue may not list
+    let assoc_mgr = |flags: &str, filter: String| {
+        slurm::output(Command::new("scontrol").args(["show", "assoc_mgr", flags, &filter]))
+            .map(|output| parse_assoc_mgr(&output))
+            .unwrap_or_default()
+    };
+    let assoc_usage = assoc_mgr("flags=assoc", format!("users={}", user));
+    let mut limits = Vec::new();
+    let mut qos_names: Vec<&str> = Vec::new();
+    for r in parse_rows(&assocs, ASSOC_FIELDS.len()) {
+        qos_names.extend(r[2].split(',').filter(|q| !q.is_empty()));
+        let usage = assoc_usage.iter().find(|a| {
+            let field = |key| a.get(key).map_or("", String::as_str);
+            field("Account") == r[0]
+                && field("Partition") == r[1]
+                && field("UserName").split('(').next() == Some(user)
+        });
+        limits.push(Limits {
+            scope: Scope::Assoc,
+            name: r[0].to_owned(),
+            partition: r[1].to_owned(),
+            limits: set_limits(&ASSOC_FIELDS[3..], &r[3..]),
+            usage: usage.cloned().unwrap_or_default(),
+        });
+    }
+    qos_names.sort_unstable();
+    qos_names.dedup();
+    if !qos_names.is_empty() {
+        let qos = slurm::output(Command::new("sacctmgr").args([
+            "--noheader",
+            "--parsable2",
+            "show",
+            "qos",
+            "where",
+            &format!("name={}", qos_names.join(",")),
+            &format!("format={}", QOS_FIELDS.join(",")),
+        ]))?;
+        let qos_usage = assoc_mgr("flags=qos", format!("qos={}", qos_names.join(",")));
+        limits.extend(parse_rows(&qos, QOS_FIELDS.len()).map(|r| {
+            let usage = qos_usage
+                .iter()
+                .find(|q| q.get("QOS").and_then(|q| q.split('(').next()) == Some(r[0]));
+            Limits {
+                scope: Scope::Qos,
+                name: r[0].to_owned(),
+                partition: String::new(),
+                limits: set_limits(&QOS_FIELDS[1..], &r[1..]),
+                usage: usage.cloned().unwrap_or_default(),
+            }
+        }));
+    }
+    Ok(Account {
+        user: user.to_owned(),
+        shares,
+        limits,
+    })
+}
+
+/// The records of `scontrol show assoc_mgr`, each starting with an unindented line like
+/// `ClusterName=c Account=lab UserName=alice(1000) Partition= …` or `QOS=normal(1)`.
+fn parse_assoc_mgr(output: &str) -> Vec<HashMap<String, String>> {
+    let mut records: Vec<HashMap<String, String>> = Vec::new();
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            records.push(HashMap::new());
+        }
+        let Some(record) = records.last_mut() else {
+            continue;
+        };
+        for (key, value) in line.split_whitespace().filter_map(|t| t.split_once('=')) {
+            record.insert(key.to_owned(), value.to_owned());
+        }
+    }
+    records.retain(|r| !r.is_empty());
+    records
+}
+
+/// Lines of `--parsable2` output with the expected number of fields.
+fn parse_rows(output: &str, fields: usize) -> impl Iterator<Item = Vec<&str>> {
+    output
+        .lines()
+        .map(|l| l.split('|').map(str::trim).collect::<Vec<_>>())
+        .filter(move |r| r.len() == fields)
+}
+
+fn set_limits(fields: &[&'static str], values: &[&str]) -> Vec<(&'static str, String)> {
+    fields
+        .iter()
+        .zip(values)
+        .filter(|(_, v)| !v.is_empty())
+        .map(|(f, v)| (*f, v.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_assoc_mgr() {
+        let records = parse_assoc_mgr(
+            "Current Association Manager state\n\nAssociation Records\n\n\
+             ClusterName=c Account=lab UserName=alice(1000) Partition= Priority=0 ID=2\n    \
+             GrpJobs=N(3) GrpSubmitJobs=50(5)\n    GrpTRES=cpu=256(48),mem=N(8192)\n\
+             QOS Records\n\nQOS=normal(1)\n    GrpJobs=10(7)\n",
+        );
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["UserName"], "alice(1000)");
+        assert_eq!(records[0]["Partition"], "");
+        assert_eq!(records[0]["GrpTRES"], "cpu=256(48),mem=N(8192)");
+        assert_eq!(records[1]["QOS"], "normal(1)");
+        assert_eq!(records[1]["GrpJobs"], "10(7)");
+    }
+}
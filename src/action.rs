@@ -0,0 +1,107 @@
+use std::{process::Command, thread};
+
+use crossbeam::channel::{Receiver, Sender, unbounded};
+
+use crate::app::AppMessage;
+use crate::job_update::JobUpdate;
+use crate::signal::Scancel;
+use crate::slurm;
+use crate::submit::Submission;
+
+/// A Slurm command that changes something, run off the UI thread.
+#[derive(Clone)]
+pub enum Action {
+    Scancel(Scancel),
+    Update(JobUpdate),
+    Submit(Submission),
+}
+
+impl Action {
+    fn command(&self) -> Command {
+        match self {
+            Action::Scancel(scancel) => {
+                let mut command = Command::new("scancel");
+                command.args(scancel.args());
+                command
+            }
+            Action::Update(update) => {
+                let mut command = Command::new("scontrol");
+                command.args(update.args());
+                command
+            }
+            Action::Submit(submission) => {
+                let mut command = Command::new("sbatch");
+                command.args(submission.args());
+                command
+            }
+        }
+    }
+
+    fn stdin(&self) -> Option<&str> {
+        match self {
+            Action::Submit(submission) => Some(&submission.script),
+            _ => None,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Action::Scancel(scancel) => scancel.description(),
+            Action::Update(update) => update.description(),
+            Action::Submit(submission) => submission.description.clone(),
+        }
+    }
+}
+
+struct ActionRunner {
+    app: Sender<AppMessage>,
+    receiver: Receiver<(usize, Action)>,
+}
+
+pub struct ActionRunnerHandle {
+    sender: Sender<(usize, Action)>,
+    next_id: usize,
+}
+
+impl ActionRunner {
+    fn new(app: Sender<AppMessage>, receiver: Receiver<(usize, Action)>) -> Self {
+        Self { app, receiver }
+    }
+
+    fn run(&mut self) {
+        for (id, action) in self.receiver.iter() {
+            if self.app.send(AppMessage::ActionStarted(id)).is_err() {
+                return;
+            }
+            let result = match action.stdin() {
+                Some(stdin) => slurm::output_with_stdin(&mut action.command(), stdin),
+                None => slurm::output(&mut action.command()),
+            };
+            if self
+                .app
+                .send(AppMessage::ActionFinished(id, result))
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+impl ActionRunnerHandle {
+    pub fn new(app: Sender<AppMessage>) -> Self {
+        let (sender, receiver) = unbounded();
+        let mut actor = ActionRunner::new(app, receiver);
+        thread::spawn(move || actor.run());
+
+        Self { sender, next_id: 0 }
+    }
+
+    /// Queues the action and returns its id.
+    pub fn run(&mut self, action: Action) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        let _ = self.sender.send((id, action));
+        id
+    }
+}
@@ -1,13 +1,50 @@
 use crossbeam::{
-    channel::{Receiver, unbounded},
+    channel::{Receiver, Sender, after, never, unbounded},
     select,
 };
 use itertools::Either;
-use std::{cmp::min, iter::once, path::PathBuf, process::Command};
-use std::{process::Stdio, time::Duration};
+use std::time::{Duration, Instant};
+use std::{
+    cmp::min,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    iter::once,
+    path::PathBuf,
+    process::Command,
+    thread,
+};
 
+use crate::account_tab::AccountTab;
+use crate::account_watcher::Account;
+use crate::action::{Action, ActionRunnerHandle};
+use crate::array_matrix::{self, AccountedTask};
+use crate::dependency::{self, Dependency};
+use crate::efficiency::{self, Efficiency};
 use crate::file_watcher::{FileWatcherError, FileWatcherHandle};
+use crate::form::{Form, FormEvent};
+use crate::highlight;
+use crate::history::{HistoryEntry, HistoryHandle};
+use crate::history_tab::HistoryTab;
+use crate::input::InputHandle;
+use crate::job_rows::{self, Row};
+use crate::job_state::JobState;
+use crate::job_update::JobEdit;
 use crate::job_watcher::JobWatcherHandle;
+use crate::node_watcher::Node;
+use crate::nodes_tab::NodesTab;
+use crate::pager::{self, Position, Viewer};
+use crate::partition_watcher::Partition;
+use crate::partitions_tab::PartitionsTab;
+use crate::pending::{self, Priority};
+use crate::scroll::{self, Scroll};
+use crate::signal::Scancel;
+use crate::slurm::{self, SlurmDuration};
+use crate::submit::{self, Resubmit, Submission};
+use crate::template::{self, Template};
+use crate::terminal;
+use crate::theme::Theme;
+use crate::tres::Tres;
+use crate::usage_watcher::{Sample, UsageWatcherHandle};
+use chrono::{DateTime, Local};
 
 use crossterm::event::{Event, KeyCode, KeyEvent};
 use ratatui::{
@@ -16,16 +53,136 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap,
+    },
 };
-use std::io;
+use std::io::{self, Write};
 
+/// The panel that gets the navigation keys, `h`/`l` move between those of the tab.
+#[derive(Clone, Copy, PartialEq)]
 pub enum Focus {
     Jobs,
+    Details,
+    Log,
+    Nodes,
+    NodeJobs,
+    Partitions,
+    Shares,
+    Limits,
+    History,
+    HistoryDetails,
+}
+
+impl Focus {
+    /// The panels of the tab, from left to right and top to bottom.
+    fn panels(tab: Tab) -> &'static [Focus] {
+        match tab {
+            Tab::Jobs => &[Focus::Jobs, Focus::Details, Focus::Log],
+            Tab::Nodes => &[Focus::Nodes, Focus::NodeJobs],
+            Tab::Partitions => &[Focus::Partitions],
+            Tab::Account => &[Focus::Shares, Focus::Limits],
+            Tab::History => &[Focus::History, Focus::HistoryDetails],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tab {
+    Jobs,
+    Nodes,
+    Partitions,
+    Account,
+    History,
+}
+
+const TABS: [Tab; 5] = [
+    Tab::Jobs,
+    Tab::Nodes,
+    Tab::Partitions,
+    Tab::Account,
+    Tab::History,
+];
+
+impl Tab {
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Jobs => "Jobs",
+            Tab::Nodes => "Nodes",
+            Tab::Partitions => "Partitions",
+            Tab::Account => "Account",
+            Tab::History => "History",
+        }
+    }
 }
 
 pub enum Dialog {
-    ConfirmCancelJob(String),
+    SignalJob {
+        job_id: String,
+        form: Form,
+    },
+    ConfirmSignal(Scancel),
+    /// `edit` is `None` while the current values are being fetched.
+    EditJob {
+        job_id: String,
+        edit: Option<Result<JobEdit, String>>,
+    },
+    /// `resubmit` is `None` while the script and options are being fetched.
+    Resubmit {
+        job_id: String,
+        resubmit: Option<Result<Resubmit, String>>,
+    },
+    /// `script` is `None` while it is being fetched.
+    BatchScript {
+        job_id: String,
+        script: Option<Result<String, String>>,
+        offset: u16,
+    },
+    ActionHistory(u16),
+    /// Running and pending jobs and their resources per user and partition.
+    Totals,
+    /// The jobs a job waits for and the jobs waiting for it, by their node ID, see [`dependency`].
+    Dependencies {
+        job_id: String,
+        offset: u16,
+    },
+    PickTemplate {
+        templates: Result<Vec<Template>, String>,
+        selected: usize,
+    },
+    TemplateVariables {
+        template: Template,
+        form: Form,
+    },
+    /// `estimate` is `None` while `sbatch --test-only` runs.
+    SubmitPreview {
+        submission: Submission,
+        estimate: Option<Result<String, String>>,
+        offset: u16,
+    },
+}
+
+pub enum ActionStatus {
+    Queued,
+    Running,
+    Succeeded(String),
+    Failed(String),
+}
+
+pub struct ActionEntry {
+    id: usize,
+    action: Action,
+    status: ActionStatus,
+    queued_at: Instant,
+}
+
+/// Toasts beyond are summed up in a line.
+const MAX_TOASTS: usize = 5;
+
+/// Notification about an action, removed once `expires` has passed.
+pub struct Toast {
+    action_id: usize,
+    expires: Option<Instant>,
 }
 
 #[derive(Clone, Copy)]
@@ -42,36 +199,146 @@ pub enum OutputFileView {
 }
 
 pub struct App {
+    tab: Tab,
     focus: Focus,
     dialog: Option<Dialog>,
     jobs: Vec<Job>,
+    /// The lines of the job list, selected by `job_list_state`.
+    rows: Vec<Row>,
+    /// Whether array tasks are grouped under their array.
+    tree: bool,
+    /// Array IDs whose tasks are listed in tree mode.
+    expanded: HashSet<String>,
     job_list_state: ListState,
+    /// Shown in place of the job list while open.
+    matrix: Option<Matrix>,
+    /// Cells per row of the matrix, as last rendered.
+    matrix_columns: u16,
     job_output: Result<String, FileWatcherError>,
+    job_details_scroll: Scroll,
     job_output_anchor: ScrollAnchor,
     job_output_offset: u16,
     job_output_wrap: bool,
-    _job_watcher: JobWatcherHandle,
+    job_watcher: JobWatcherHandle,
     job_output_watcher: FileWatcherHandle,
-    // sender: Sender<AppMessage>,
+    usage_watcher: UsageWatcherHandle,
+    /// Usage samples of the selected job, while it runs.
+    usage: Option<JobUsage>,
+    efficiency: Option<EfficiencyInfo>,
+    action_runner: ActionRunnerHandle,
+    actions: Vec<ActionEntry>,
+    toasts: Vec<Toast>,
+    sender: Sender<AppMessage>,
     receiver: Receiver<AppMessage>,
-    input_receiver: Receiver<std::io::Result<Event>>,
+    input: InputHandle,
     output_file_view: OutputFileView,
     job_list_height: u16,
+    dialog_height: u16,
+    /// Lines of the content of a scrollable dialog, as last rendered.
+    dialog_lines: u16,
+    /// Job ID to select once it appears in the job list.
+    pending_selection: Option<String>,
+    /// Shell command to attach to a running job, see `--attach-command`.
+    attach_command: String,
+    /// Interactive command to run with the TUI suspended.
+    foreground: Option<Foreground>,
+    /// How long vanished jobs stay in the list.
+    keep_vanished: Duration,
+    vanished: HashMap<String, Vanished>,
+    theme: Theme,
+    /// Running jobs with less time left are highlighted, see `--limit-margin`.
+    limit_margin: Duration,
+    /// Whether the job list shows the time left.
+    show_remaining: bool,
+    /// IDs of the jobs marked with space, whose resources are summed up.
+    marked: HashSet<String>,
+    pending_info: Option<PendingInfo>,
+    history: HistoryHandle,
+    nodes_tab: NodesTab,
+    partitions_tab: PartitionsTab,
+    account_tab: AccountTab,
+    history_tab: HistoryTab,
+    /// Refresh rate for the watchers of the Nodes and Partitions tabs.
+    node_refresh: Duration,
+    /// Much slower, fairshare and limits change little and `sacctmgr` is expensive.
+    account_refresh: Duration,
+}
+
+/// A job that dropped out of squeue, kept in the list for a while.
+pub struct Vanished {
+    since: Instant,
+    /// From sacct, `None` while it is being looked up.
+    final_state: Option<Result<String, String>>,
+}
+
+/// The tasks of an array as a grid of cells colored by state.
+pub struct Matrix {
+    array_id: String,
+    /// Index of the task under the cursor, whose details and log are shown.
+    cursor: u32,
+    /// Tasks that left the queue are only in accounting.
+    /// `None` while being looked up for the first time.
+    accounted: Option<Result<Vec<AccountedTask>, String>>,
+    since: Instant,
+}
+
+/// Samples of `sstat`, at most [`USAGE_SAMPLES`].
+pub struct JobUsage {
+    job_id: String,
+    samples: VecDeque<Sample>,
+    /// Of the last lookup.
+    error: Option<String>,
+}
+
+const USAGE_SAMPLES: usize = 30;
+
+/// The efficiency of the selected finished job, from the Jobs or the History tab.
+pub struct EfficiencyInfo {
+    id: String,
+    /// `None` while being looked up.
+    report: Option<Result<Efficiency, String>>,
+}
+
+/// Live information on the selected pending job, see [`pending`].
+pub struct PendingInfo {
+    id: String,
+    reason: Option<String>,
+    since: Instant,
+    /// Both are `None` while being looked up.
+    context: Option<Result<Option<String>, String>>,
+    priority: Option<Result<Priority, String>>,
+}
+
+pub struct Foreground {
+    command: Command,
+    description: String,
 }
 
+#[derive(Clone)]
 pub struct Job {
     pub job_id: String,
     pub array_id: String,
     pub array_step: Option<String>,
     pub name: String,
-    pub state: String,
+    pub state: JobState,
     pub state_compact: String,
     pub reason: Option<String>,
     pub user: String,
-    pub time: String,
-    pub start_time: String,
-    pub tres: String,
+    /// Time used.
+    pub time: SlurmDuration,
+    /// `None` if unlimited.
+    pub time_limit: Option<SlurmDuration>,
+    pub submit_time: Option<DateTime<Local>>,
+    /// Expected, while the job is pending.
+    pub start_time: Option<DateTime<Local>>,
+    /// Expected, from the time limit, while the job is not finished.
+    pub end_time: Option<DateTime<Local>>,
+    pub tres: Tres,
+    /// Those that are not satisfied yet.
+    pub dependencies: Vec<Dependency>,
     pub partition: String,
+    pub account: String,
+    pub qos: String,
     pub nodelist: String,
     pub stdout: Option<PathBuf>,
     pub stderr: Option<PathBuf>,
@@ -79,39 +346,116 @@ pub struct Job {
 }
 
 impl Job {
-    fn id(&self) -> String {
+    pub fn id(&self) -> String {
         match self.array_step.as_ref() {
             Some(array_step) => format!("{}_{}", self.array_id, array_step),
             None => self.job_id.clone(),
         }
     }
+
+    /// The time left until the time limit of a running job.
+    pub fn remaining(&self) -> Option<Duration> {
+        match self.state {
+            JobState::Running => Some(self.time_limit?.0.saturating_sub(self.time.0)),
+            _ => None,
+        }
+    }
+
+    /// A running job with the given ID and nothing else set, for tests.
+    #[cfg(test)]
+    pub fn test(job_id: &str) -> Self {
+        Job {
+            job_id: job_id.to_owned(),
+            array_id: job_id.to_owned(),
+            array_step: None,
+            name: String::new(),
+            state: JobState::Running,
+            state_compact: "R".to_owned(),
+            reason: None,
+            user: String::new(),
+            time: SlurmDuration::default(),
+            time_limit: None,
+            submit_time: None,
+            start_time: None,
+            end_time: None,
+            tres: Tres::default(),
+            dependencies: Vec::new(),
+            partition: String::new(),
+            account: String::new(),
+            qos: String::new(),
+            nodelist: String::new(),
+            stdout: None,
+            stderr: None,
+            command: String::new(),
+        }
+    }
 }
 
 pub enum AppMessage {
     Jobs(Vec<Job>),
     JobOutput(Result<String, FileWatcherError>),
+    JobInfo(String, Result<HashMap<String, String>, String>),
+    BatchScript(String, Result<String, String>),
+    Resubmit(String, Result<Resubmit, String>),
+    /// Start estimate for the script of a submission.
+    TestOnly(String, Result<String, String>),
+    FinalState(String, Result<String, String>),
+    PendingInfo(
+        String,
+        Option<String>,
+        Result<Option<String>, String>,
+        Result<Priority, String>,
+    ),
+    /// All history entries, and the last error of the history store.
+    History(Vec<HistoryEntry>, Option<String>),
+    Nodes(Result<Vec<Node>, String>),
+    Partitions(Result<Vec<Partition>, String>),
+    Account(Result<Account, String>),
+    Usage(String, Result<Sample, String>),
+    Efficiency(String, Result<Efficiency, String>),
+    ArrayTasks(String, Result<Vec<AccountedTask>, String>),
+    ActionStarted(usize),
+    ActionFinished(usize, Result<String, String>),
     Key(KeyEvent),
 }
 
 impl App {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        input_receiver: Receiver<std::io::Result<Event>>,
+        input: InputHandle,
         slurm_refresh_rate: u64,
+        node_refresh_rate: u64,
+        account_refresh_rate: u64,
+        usage_refresh_rate: u64,
         file_refresh_rate: u64,
         squeue_args: Vec<String>,
+        attach_command: String,
+        keep_vanished: u64,
+        history_days: u64,
+        history_all_users: bool,
+        limit_margin: u64,
+        theme: Theme,
+        initial_selection: Option<String>,
     ) -> App {
         let (sender, receiver) = unbounded();
         Self {
+            tab: Tab::Jobs,
             focus: Focus::Jobs,
             dialog: None,
             jobs: Vec::new(),
-            _job_watcher: JobWatcherHandle::new(
+            rows: Vec::new(),
+            tree: false,
+            expanded: HashSet::new(),
+            job_watcher: JobWatcherHandle::new(
                 sender.clone(),
                 Duration::from_secs(slurm_refresh_rate),
                 squeue_args,
             ),
             job_list_state: ListState::default(),
+            matrix: None,
+            matrix_columns: 1,
             job_output: Ok("".to_string()),
+            job_details_scroll: Scroll::default(),
             job_output_anchor: ScrollAnchor::Bottom,
             job_output_offset: 0,
             job_output_wrap: false,
@@ -119,11 +463,39 @@ impl App {
                 sender.clone(),
                 Duration::from_secs(file_refresh_rate),
             ),
-            // sender,
+            usage_watcher: UsageWatcherHandle::new(
+                sender.clone(),
+                Duration::from_secs(usage_refresh_rate),
+            ),
+            usage: None,
+            efficiency: None,
+            action_runner: ActionRunnerHandle::new(sender.clone()),
+            history: HistoryHandle::new(sender.clone(), history_days, history_all_users),
+            actions: Vec::new(),
+            toasts: Vec::new(),
+            sender,
             receiver,
-            input_receiver,
+            input,
             output_file_view: OutputFileView::default(),
             job_list_height: 0,
+            dialog_height: 0,
+            dialog_lines: 0,
+            pending_selection: initial_selection,
+            attach_command,
+            foreground: None,
+            keep_vanished: Duration::from_secs(keep_vanished),
+            vanished: HashMap::new(),
+            theme,
+            limit_margin: Duration::from_secs(limit_margin * 60),
+            show_remaining: false,
+            marked: HashSet::new(),
+            pending_info: None,
+            nodes_tab: NodesTab::new(),
+            partitions_tab: PartitionsTab::new(),
+            account_tab: AccountTab::new(),
+            history_tab: HistoryTab::new(),
+            node_refresh: Duration::from_secs(node_refresh_rate),
+            account_refresh: Duration::from_secs(account_refresh_rate),
         }
     }
 }
@@ -133,20 +505,41 @@ impl App {
         terminal.draw(|f| self.ui(f))?;
 
         loop {
+            let toast_timer = self
+                .toasts
+                .iter()
+                .filter_map(|t| t.expires)
+                .min()
+                .map_or(never(), |t| {
+                    after(t.saturating_duration_since(Instant::now()))
+                });
+
             select! {
                 recv(self.receiver) -> event => {
                     self.handle(event.unwrap());
                 }
-                recv(self.input_receiver) -> input_res => {
-                    match input_res.unwrap().unwrap() {
+                recv(toast_timer) -> _ => {
+                    let now = Instant::now();
+                    self.toasts.retain(|t| t.expires.is_none_or(|e| e > now));
+                }
+                recv(self.input.receiver) -> input_res => {
+                    let redraw = match input_res.unwrap().unwrap() {
                         Event::Key(key) => {
-                            if key.code == KeyCode::Char('q') {
+                            if key.code == KeyCode::Char('q') && !self.captures_input() {
                                 return Ok(());
                             }
                             self.handle(AppMessage::Key(key));
+                            true
                         },
-                        Event::Resize(_, _) => {},
-                        _ => continue, // ignore and do not redraw
+                        Event::Resize(_, _) => true,
+                        _ => false, // ignore and do not redraw
+                    };
+                    if let Some(mut foreground) = self.foreground.take() {
+                        terminal::suspend(terminal, || run_foreground(&mut foreground))?;
+                    }
+                    self.input.handled();
+                    if !redraw {
+                        continue;
                     }
                 }
             };
@@ -157,315 +550,1682 @@ impl App {
 
     fn handle(&mut self, msg: AppMessage) {
         match msg {
-            AppMessage::Jobs(jobs) => {
+            AppMessage::Jobs(mut jobs) => {
                 // On refresh: keep the same job selected if it still exists
-                let old_index = self.job_list_state.selected();
-                let old_id = old_index.and_then(|i| self.jobs.get(i)).map(|j| j.id());
+                let selection = self.selection();
+
+                self.history.observe(&jobs);
+                if self.tab == Tab::History {
+                    self.history.request();
+                }
+
+                // look up how jobs that left the queue ended, and keep them for a while, their
+                // log is often still being read
+                let now = Instant::now();
+                let ids: HashSet<String> = jobs.iter().map(|j| j.id()).collect();
+                let mut kept = HashSet::new();
+                for job in std::mem::take(&mut self.jobs) {
+                    let id = job.id();
+                    if ids.contains(&id) {
+                        continue;
+                    }
+                    if !self.vanished.contains_key(&id) {
+                        self.spawn_query({
+                            let id = id.clone();
+                            let job_id = job.job_id.clone();
+                            move || AppMessage::FinalState(id, slurm::final_state(&job_id))
+                        });
+                        self.vanished.insert(
+                            id.clone(),
+                            Vanished {
+                                since: now,
+                                final_state: None,
+                            },
+                        );
+                    }
+                    if now.duration_since(self.vanished[&id].since) < self.keep_vanished {
+                        kept.insert(id);
+                        jobs.push(job);
+                    }
+                }
+                self.vanished.retain(|id, _| kept.contains(id));
 
                 self.jobs = jobs;
+                let ids: HashSet<String> = self.jobs.iter().map(|j| j.id()).collect();
+                self.marked.retain(|id| ids.contains(id));
+                let arrays: HashSet<&String> = self.jobs.iter().map(|j| &j.array_id).collect();
+                self.expanded.retain(|id| arrays.contains(id));
+                self.update_rows(selection);
+                if self.tab == Tab::Nodes {
+                    self.update_node_jobs();
+                }
 
-                if self.jobs.is_empty() {
-                    self.job_list_state.select(None);
-                } else if let Some(id) = old_id {
-                    let new_index = self
-                        .jobs
-                        .iter()
-                        .position(|j| j.id() == id)
-                        .unwrap_or(old_index.unwrap_or(0).min(self.jobs.len() - 1));
-                    self.job_list_state.select(Some(new_index));
-                } else {
-                    self.job_list_state.select_first();
+                // select a freshly submitted job as soon as it shows up
+                if let Some(id) = &self.pending_selection {
+                    if let Some(i) = self.rows.iter().position(|r| match r {
+                        Row::Job(i) => self.jobs[*i].job_id == *id || self.jobs[*i].array_id == *id,
+                        Row::Array { array_id, .. } => array_id == id,
+                    }) {
+                        self.job_list_state.select(Some(i));
+                        self.pending_selection = None;
+                    }
                 }
             }
             AppMessage::JobOutput(content) => self.job_output = content,
-            AppMessage::Key(key) => {
-                if let Some(dialog) = &self.dialog {
-                    match dialog {
-                        Dialog::ConfirmCancelJob(id) => match key.code {
-                            KeyCode::Enter | KeyCode::Char('y') => {
-                                let id = id.clone();
-                                Command::new("scancel")
-                                    .arg(id)
-                                    .stdout(Stdio::null())
-                                    .stderr(Stdio::null())
-                                    .status()
-                                    .expect("failed to execute scancel");
-                                self.dialog = None;
-                            }
-                            KeyCode::Esc => {
-                                self.dialog = None;
-                            }
-                            _ => {}
-                        },
-                    };
-                } else {
-                    match key.code {
-                        KeyCode::Char('h') | KeyCode::Left => self.focus_previous_panel(),
-                        KeyCode::Char('l') | KeyCode::Right => self.focus_next_panel(),
-                        KeyCode::Char('k') | KeyCode::Up => match self.focus {
-                            Focus::Jobs => self.select_previous_job(),
-                        },
-                        KeyCode::Char('j') | KeyCode::Down => match self.focus {
-                            Focus::Jobs => self.select_next_job(),
-                        },
-                        KeyCode::Char('g') => match self.focus {
-                            Focus::Jobs => self.select_first_job(),
-                        },
-                        KeyCode::Char('G') => match self.focus {
-                            Focus::Jobs => self.select_last_job(),
-                        },
-                        KeyCode::Char('u') => match self.focus {
-                            Focus::Jobs => {
-                                if key
-                                    .modifiers
-                                    .contains(crossterm::event::KeyModifiers::CONTROL)
-                                {
-                                    self.scroll_jobs_half_page_up()
-                                }
-                            }
-                        },
-                        KeyCode::Char('d') => match self.focus {
-                            Focus::Jobs => {
-                                if key
-                                    .modifiers
-                                    .contains(crossterm::event::KeyModifiers::CONTROL)
-                                {
-                                    self.scroll_jobs_half_page_down()
-                                }
-                            }
-                        },
-                        KeyCode::PageDown => {
-                            let delta = if key.modifiers.intersects(
-                                crossterm::event::KeyModifiers::SHIFT
-                                    | crossterm::event::KeyModifiers::CONTROL
-                                    | crossterm::event::KeyModifiers::ALT,
-                            ) {
-                                50
-                            } else {
-                                1
-                            };
-                            match self.job_output_anchor {
-                                ScrollAnchor::Top => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_add(delta)
-                                }
-                                ScrollAnchor::Bottom => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_sub(delta)
-                                }
-                            }
-                        }
-                        KeyCode::PageUp => {
-                            let delta = if key.modifiers.intersects(
-                                crossterm::event::KeyModifiers::SHIFT
-                                    | crossterm::event::KeyModifiers::CONTROL
-                                    | crossterm::event::KeyModifiers::ALT,
-                            ) {
-                                50
-                            } else {
-                                1
-                            };
-                            match self.job_output_anchor {
-                                ScrollAnchor::Top => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_sub(delta)
-                                }
-                                ScrollAnchor::Bottom => {
-                                    self.job_output_offset =
-                                        self.job_output_offset.saturating_add(delta)
-                                }
-                            }
-                        }
-                        KeyCode::Home => {
-                            self.job_output_offset = 0;
-                            self.job_output_anchor = ScrollAnchor::Top;
-                        }
-                        KeyCode::End => {
-                            self.job_output_offset = 0;
-                            self.job_output_anchor = ScrollAnchor::Bottom;
-                        }
-                        KeyCode::Char('c') => {
-                            if let Some(id) = self
-                                .job_list_state
-                                .selected()
-                                .and_then(|i| self.jobs.get(i).map(|j| j.id()))
-                            {
-                                self.dialog = Some(Dialog::ConfirmCancelJob(id));
+            AppMessage::JobInfo(id, info) => {
+                if let Some(Dialog::EditJob { job_id, edit }) = &mut self.dialog {
+                    if *job_id == id && edit.is_none() {
+                        *edit = Some(info.map(|info| JobEdit::new(&id, &info)));
+                    }
+                }
+            }
+            AppMessage::BatchScript(id, result) => {
+                if let Some(Dialog::BatchScript { job_id, script, .. }) = &mut self.dialog {
+                    if *job_id == id {
+                        *script = Some(result);
+                    }
+                }
+            }
+            AppMessage::Resubmit(id, result) => {
+                if let Some(Dialog::Resubmit { job_id, resubmit }) = &mut self.dialog {
+                    if *job_id == id && resubmit.is_none() {
+                        *resubmit = Some(result);
+                    }
+                }
+            }
+            AppMessage::TestOnly(script, result) => {
+                if let Some(Dialog::SubmitPreview {
+                    submission,
+                    estimate,
+                    ..
+                }) = &mut self.dialog
+                {
+                    if submission.script == script && estimate.is_none() {
+                        *estimate = Some(result);
+                    }
+                }
+            }
+            AppMessage::FinalState(id, result) => {
+                if let Ok(state) = &result {
+                    self.history.final_state(id.clone(), state.clone());
+                }
+                if let Some(vanished) = self.vanished.get_mut(&id) {
+                    vanished.final_state = Some(result);
+                }
+            }
+            AppMessage::PendingInfo(id, reason, context, priority) => {
+                if let Some(info) = &mut self.pending_info {
+                    if info.id == id && info.reason == reason {
+                        info.context = Some(context);
+                        info.priority = Some(priority);
+                    }
+                }
+            }
+            AppMessage::History(entries, error) => self.history_tab.set_entries(entries, error),
+            AppMessage::Nodes(nodes) => self.nodes_tab.set_nodes(nodes),
+            AppMessage::Partitions(partitions) => self.partitions_tab.set_partitions(partitions),
+            AppMessage::Account(account) => self.account_tab.set_account(account),
+            AppMessage::Efficiency(id, report) => {
+                if let Some(info) = self.efficiency.as_mut().filter(|i| i.id == id) {
+                    info.report = Some(report);
+                }
+            }
+            AppMessage::ArrayTasks(array_id, tasks) => {
+                if let Some(matrix) = self.matrix.as_mut().filter(|m| m.array_id == array_id) {
+                    matrix.accounted = Some(tasks);
+                }
+            }
+            AppMessage::Usage(job_id, sample) => {
+                if let Some(usage) = self.usage.as_mut().filter(|u| u.job_id == job_id) {
+                    match sample {
+                        Ok(sample) => {
+                            if usage.samples.len() == USAGE_SAMPLES {
+                                usage.samples.pop_front();
                             }
+                            usage.samples.push_back(sample);
+                            usage.error = None;
                         }
-                        KeyCode::Char('o') => {
-                            self.output_file_view = match self.output_file_view {
-                                OutputFileView::Stdout => OutputFileView::Stderr,
-                                OutputFileView::Stderr => OutputFileView::Stdout,
-                            };
-                        }
-                        KeyCode::Char('w') => {
-                            self.job_output_wrap = !self.job_output_wrap;
-                        }
-                        _ => {}
+                        Err(e) => usage.error = Some(e),
+                    }
+                }
+            }
+            AppMessage::ActionStarted(id) => {
+                if let Some(entry) = self.actions.iter_mut().find(|a| a.id == id) {
+                    entry.status = ActionStatus::Running;
+                }
+            }
+            AppMessage::ActionFinished(id, result) => {
+                let failed = result.is_err();
+                if !failed {
+                    // show the effect of the action right away
+                    self.job_watcher.refresh();
+                }
+                if let Some(entry) = self.actions.iter_mut().find(|a| a.id == id) {
+                    if let (Action::Submit(_), Ok(output)) = (&entry.action, &result) {
+                        self.pending_selection = submit::submitted_job_id(output).map(String::from);
+                    }
+                    entry.status = match result {
+                        Ok(output) => ActionStatus::Succeeded(output),
+                        Err(e) => ActionStatus::Failed(e),
                     };
                 }
+                if let Some(toast) = self.toasts.iter_mut().find(|t| t.action_id == id) {
+                    // keep errors around for longer, they need to be read
+                    let duration = Duration::from_secs(if failed { 10 } else { 4 });
+                    toast.expires = Some(Instant::now() + duration);
+                }
+            }
+            AppMessage::Key(key) => {
+                if let Some(dialog) = self.dialog.take() {
+                    self.dialog = self.handle_dialog_key(dialog, key);
+                } else if !self.handle_tab_key(key) {
+                    if let Some(tab) = match key.code {
+                        KeyCode::Char(c @ '1'..='9') => {
+                            TABS.get(c as usize - '1' as usize).copied()
+                        }
+                        KeyCode::Tab => TABS
+                            .iter()
+                            .cycle()
+                            .skip_while(|t| **t != self.tab)
+                            .nth(1)
+                            .copied(),
+                        KeyCode::BackTab => TABS
+                            .iter()
+                            .rev()
+                            .cycle()
+                            .skip_while(|t| **t != self.tab)
+                            .nth(1)
+                            .copied(),
+                        _ => None,
+                    } {
+                        self.select_tab(tab);
+                    }
+                }
             }
         }
 
         // update
         self.job_output_watcher
-            .set_file_path(self.job_list_state.selected().and_then(|i| {
-                self.jobs.get(i).and_then(|j| match self.output_file_view {
-                    OutputFileView::Stdout => j.stdout.clone(),
-                    OutputFileView::Stderr => j.stderr.clone(),
-                })
-            }));
+            .set_file_path(self.selected_output_path());
+        self.update_pending_info();
+        self.update_efficiency();
+        self.update_matrix();
+
+        let running = self
+            .selected_job()
+            .filter(|j| j.state == JobState::Running && !self.vanished.contains_key(&j.id()))
+            .map(|j| j.job_id.clone());
+        if self.usage.as_ref().map(|u| &u.job_id) != running.as_ref() {
+            self.usage = running.clone().map(|job_id| JobUsage {
+                job_id,
+                samples: VecDeque::new(),
+                error: None,
+            });
+        }
+        self.usage_watcher.set_job_id(running);
     }
 
-    fn ui(&mut self, f: &mut Frame) {
-        // Layout
+    /// The final state of a vanished job once it is known, otherwise the one from squeue.
+    /// Returns the state and its compact name.
+    fn shown_state(&self, job: &Job) -> (JobState, String) {
+        match self
+            .vanished
+            .get(&job.id())
+            .and_then(|v| v.final_state.as_ref()?.as_ref().ok())
+            .map(|s| JobState::parse(s))
+            .filter(|s| !matches!(s, JobState::Unknown(_)))
+        {
+            Some(state) => {
+                let compact = state.compact().to_owned();
+                (state, compact)
+            }
+            None => (job.state.clone(), job.state_compact.clone()),
+        }
+    }
 
-        let content_help = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Min(3), Constraint::Length(1)].as_ref())
-            .split(f.area());
+    fn task_states(&self, tasks: &[usize]) -> Vec<JobState> {
+        tasks
+            .iter()
+            .map(|i| self.shown_state(&self.jobs[*i]).0)
+            .collect()
+    }
 
-        let master_detail = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Min(50), Constraint::Percentage(70)].as_ref())
-            .split(content_help[0]);
+    /// The number of tasks per state, e.g. `412 R / 500 PD / 88 CD`.
+    fn state_count_spans(&self, states: &[JobState]) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+        for (state, count) in job_rows::state_counts(states) {
+            if !spans.is_empty() {
+                spans.push(Span::raw(" / "));
+            }
+            spans.push(Span::styled(
+                format!("{} {}", count, state.compact()),
+                self.theme.state(state),
+            ));
+        }
+        spans
+    }
 
-        let job_detail_log = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Length(7), Constraint::Min(3)].as_ref())
-            .split(master_detail[1]);
+    /// The state, ID and name of a node of the dependency graph, with the tasks per state
+    /// for arrays. Jobs that are not listed are only known by their ID.
+    fn dependency_node_spans(&self, id: &str) -> Vec<Span<'static>> {
+        let jobs: Vec<usize> = (0..self.jobs.len())
+            .filter(|i| dependency::overlaps(id, &self.jobs[*i].id()))
+            .collect();
+        let mut spans = match (jobs.first(), id.ends_with("_*")) {
+            (None, _) => vec![Span::styled(
+                "?  ",
+                Style::default().add_modifier(Modifier::DIM),
+            )],
+            (Some(_), true) => {
+                let mut spans = self.state_count_spans(&self.task_states(&jobs));
+                spans.push(Span::raw(" "));
+                spans
+            }
+            (Some(i), false) => {
+                let (state, compact) = self.shown_state(&self.jobs[*i]);
+                vec![Span::styled(
+                    format!("{:<3}", compact),
+                    self.theme.state(&state),
+                )]
+            }
+        };
+        spans.push(Span::styled(
+            id.to_owned(),
+            Style::default().fg(Color::Yellow),
+        ));
+        if let Some(i) = jobs.first() {
+            spans.push(Span::raw(format!(" {}", self.jobs[*i].name)));
+        }
+        spans
+    }
 
-        // Help
-        let help_options = vec![
-            ("q", "quit"),
-            ("⏶/⏷", "navigate"),
-            ("pgup/pgdown", "scroll"),
-            ("home/end", "top/bottom"),
-            ("esc", "cancel"),
-            ("enter", "confirm"),
-            ("c", "cancel job"),
-            ("o", "toggle stdout/stderr"),
-            ("w", "toggle text wrap"),
+    /// The details of the selected array in tree mode.
+    fn array_detail(&self) -> Option<Text<'static>> {
+        let Some(Row::Array {
+            array_id, tasks, ..
+        }) = self.selected_row()
+        else {
+            return None;
+        };
+        let first = &self.jobs[tasks[0]];
+        let mut tasks_spans = vec![
+            Span::styled("Tasks  ", Style::default().fg(Color::Yellow)),
+            Span::raw(" "),
         ];
-        let blue_style = Style::default().fg(Color::Blue);
-        let light_blue_style = Style::default().fg(Color::LightBlue);
+        tasks_spans.extend(self.state_count_spans(&self.task_states(tasks)));
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Array  ", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::raw(format!("{} ({} tasks listed)", array_id, tasks.len())),
+            ]),
+            Line::from(tasks_spans),
+            Line::from(vec![
+                Span::styled("Name   ", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::raw(first.name.clone()),
+            ]),
+            Line::from(vec![
+                Span::styled("Command", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::raw(first.command.clone()),
+            ]),
+        ];
+        let tres: Tres = tasks.iter().map(|i| &self.jobs[*i].tres).sum();
+        lines.extend(tres_lines(&tres));
+        Some(Text::from(lines))
+    }
 
-        let help = Line::from(help_options.iter().fold(
-            Vec::new(),
-            |mut acc, (key, description)| {
-                if !acc.is_empty() {
-                    acc.push(Span::raw(" | "));
-                }
-                acc.push(Span::styled(*key, blue_style));
-                acc.push(Span::raw(": "));
-                acc.push(Span::styled(*description, light_blue_style));
-                acc
-            },
-        ));
+    /// Looks up the tasks of the array in the matrix in accounting, again every 30 seconds.
+    fn update_matrix(&mut self) {
+        let Some(matrix) = self.matrix.as_mut() else {
+            return;
+        };
+        if matrix.accounted.is_some() && matrix.since.elapsed() < Duration::from_secs(30) {
+            return;
+        }
+        matrix.since = Instant::now();
+        let array_id = matrix.array_id.clone();
+        self.spawn_query(move || {
+            let tasks = array_matrix::accounted_tasks(&array_id);
+            AppMessage::ArrayTasks(array_id, tasks)
+        });
+    }
 
-        let help = Paragraph::new(help);
-        f.render_widget(help, content_help[1]);
+    /// The task indices of the array with their state and their job, if it is still listed.
+    fn matrix_cells(&self, matrix: &Matrix) -> Vec<(u32, JobState, Option<usize>)> {
+        let mut cells: BTreeMap<u32, (JobState, Option<usize>)> = BTreeMap::new();
+        if let Some(Ok(tasks)) = &matrix.accounted {
+            for task in tasks {
+                cells.insert(task.index, (task.state.clone(), None));
+            }
+        }
+        for (i, job) in self.jobs.iter().enumerate() {
+            let Some(step) = job
+                .array_step
+                .as_ref()
+                .filter(|_| job.array_id == matrix.array_id)
+            else {
+                continue;
+            };
+            let state = self.shown_state(job).0;
+            for index in array_matrix::parse_indices(step) {
+                cells.insert(index, (state.clone(), Some(i)));
+            }
+        }
+        cells
+            .into_iter()
+            .map(|(index, (state, job))| (index, state, job))
+            .collect()
+    }
 
-        // Jobs
-        let max_id_len = self.jobs.iter().map(|j| j.id().len()).max().unwrap_or(0);
-        let max_user_len = self.jobs.iter().map(|j| j.user.len()).max().unwrap_or(0);
-        let max_partition_len = self
-            .jobs
-            .iter()
-            .map(|j| j.partition.len())
-            .max()
-            .unwrap_or(0);
-        let max_time_len = self.jobs.iter().map(|j| j.time.len()).max().unwrap_or(0);
-        let max_state_compact_len = self
-            .jobs
+    /// The details of the task under the cursor of the matrix, once it left the queue.
+    fn matrix_detail(&self) -> Option<Text<'static>> {
+        let matrix = self.matrix.as_ref()?;
+        let task = match &matrix.accounted {
+            None => {
+                return Some(Text::styled(
+                    "…",
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+            Some(Err(e)) => return Some(Text::styled(e.clone(), Style::default().fg(Color::Red))),
+            Some(Ok(tasks)) => tasks.iter().find(|t| t.index == matrix.cursor)?,
+        };
+        Some(Text::from(vec![
+            Line::from(vec![
+                Span::styled("Task   ", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::raw(format!("{}_{}", matrix.array_id, task.index)),
+            ]),
+            Line::from(vec![
+                Span::styled("State  ", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::styled(task.state.to_string(), self.theme.state(&task.state)),
+                Span::styled(" Exit ", Style::default().fg(Color::Yellow)),
+                Span::raw(task.exit_code.clone()),
+            ]),
+            Line::styled(
+                "Not listed by squeue, only in accounting.",
+                Style::default().add_modifier(Modifier::DIM),
+            ),
+        ]))
+    }
+
+    /// Returns whether the key was consumed.
+    fn handle_matrix_key(&mut self, key: KeyEvent) -> bool {
+        let Some(matrix) = &self.matrix else {
+            return false;
+        };
+        let cells = self.matrix_cells(matrix);
+        let position = cells
             .iter()
-            .map(|j| j.state_compact.len())
-            .max()
+            .position(|(index, ..)| *index == matrix.cursor)
             .unwrap_or(0);
-        let jobs: Vec<ListItem> = self
+        let columns = self.matrix_columns as usize;
+        let position = match key.code {
+            KeyCode::Char('h') | KeyCode::Left => position.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => position + 1,
+            KeyCode::Char('k') | KeyCode::Up => position.saturating_sub(columns),
+            KeyCode::Char('j') | KeyCode::Down => position + columns,
+            KeyCode::Char('g') => 0,
+            KeyCode::Char('G') => cells.len().saturating_sub(1),
+            KeyCode::Esc | KeyCode::Char('M') => {
+                self.matrix = None;
+                return true;
+            }
+            _ => return false,
+        };
+        if let Some((index, ..)) = cells.get(position.min(cells.len().saturating_sub(1))) {
+            self.matrix.as_mut().unwrap().cursor = *index;
+        }
+        true
+    }
+
+    /// Looks up the efficiency of the selected finished job once.
+    fn update_efficiency(&mut self) {
+        let id = match self.tab {
+            Tab::History => self
+                .history_tab
+                .selected()
+                .filter(|e| JobState::parse(e.state()).is_finished())
+                .map(|e| e.id.clone()),
+            _ => self
+                .selected_job()
+                .map(|j| j.id())
+                .filter(|id| self.vanished.contains_key(id)),
+        };
+        let Some(id) = id else {
+            self.efficiency = None;
+            return;
+        };
+        if self.efficiency.as_ref().is_some_and(|i| i.id == id) {
+            return;
+        }
+        self.efficiency = Some(EfficiencyInfo {
+            id: id.clone(),
+            report: None,
+        });
+        self.spawn_query(move || {
+            let report = efficiency::efficiency(&id);
+            AppMessage::Efficiency(id, report)
+        });
+    }
+
+    /// Hands the jobs still in the queue to the Nodes tab, which lists them per node.
+    fn update_node_jobs(&mut self) {
+        let queued = self
             .jobs
             .iter()
-            .map(|j| {
-                ListItem::new(Line::from(vec![
-                    Span::styled(
-                        format!(
-                            "{:<max$.max$}",
-                            j.state_compact,
-                            max = max_state_compact_len
-                        ),
-                        Style::default(),
-                    ),
-                    Span::raw(" "),
-                    Span::styled(
-                        format!("{:<max$.max$}", j.id(), max = max_id_len),
-                        Style::default().fg(Color::Yellow),
-                    ),
-                    Span::raw(" "),
-                    Span::styled(
-                        format!("{:<max$.max$}", j.partition, max = max_partition_len),
-                        Style::default().fg(Color::Blue),
-                    ),
-                    Span::raw(" "),
-                    Span::styled(
-                        format!("{:<max$.max$}", j.user, max = max_user_len),
-                        Style::default().fg(Color::Green),
-                    ),
-                    Span::raw(" "),
-                    Span::styled(
-                        format!("{:>max$.max$}", j.time, max = max_time_len),
-                        Style::default().fg(Color::Red),
-                    ),
-                    Span::raw(" "),
-                    Span::raw(&j.name),
-                ]))
-            })
-            .collect();
-        let job_list = List::new(jobs)
-            .block(
-                Block::default()
-                    .title(format!("─Jobs ({})", self.jobs.len()))
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(if self.dialog.is_some() {
-                        Style::default()
-                    } else {
-                        match self.focus {
-                            Focus::Jobs => Style::default().fg(Color::Green),
-                        }
-                    }),
-            )
-            .highlight_style(Style::default().bg(Color::Green).fg(Color::Black));
-        f.render_stateful_widget(job_list, master_detail[0], &mut self.job_list_state);
-        self.job_list_height = master_detail[0].height.saturating_sub(2); // account for borders
-
-        // Job details
+            .filter(|j| !self.vanished.contains_key(&j.id()));
+        self.nodes_tab.set_jobs(queued);
+    }
 
-        let job_detail = self
-            .job_list_state
-            .selected()
-            .and_then(|i| self.jobs.get(i));
+    /// Looks up the priority and reason context of the selected pending job, again every 30 seconds.
+    fn update_pending_info(&mut self) {
+        let Some(job) = self
+            .selected_job()
+            .filter(|j| j.state == JobState::Pending && !self.vanished.contains_key(&j.id()))
+            .cloned()
+        else {
+            self.pending_info = None;
+            return;
+        };
+        let id = job.id();
+        let current = self
+            .pending_info
+            .as_ref()
+            .filter(|i| i.id == id && i.reason == job.reason);
+        if current.is_some_and(|i| i.since.elapsed() < Duration::from_secs(30)) {
+            return;
+        }
+        // keep showing the previous information while refreshing it
+        self.pending_info = Some(PendingInfo {
+            id: id.clone(),
+            reason: job.reason.clone(),
+            since: Instant::now(),
+            context: current.and_then(|i| i.context.clone()),
+            priority: current.and_then(|i| i.priority.clone()),
+        });
+        self.spawn_query(move || {
+            AppMessage::PendingInfo(
+                id,
+                job.reason.clone(),
+                pending::context(&job),
+                pending::priority(&job),
+            )
+        });
+    }
 
-        let job_detail = job_detail.map(|j| {
-            let mut state_spans = vec![
-                Span::styled("State  ", Style::default().fg(Color::Yellow)),
-                Span::raw(" "),
-                Span::raw(&j.state),
-            ];
-            if j.state == "PENDING" {
-                state_spans.extend([
-                    Span::styled(" Start ", Style::default().fg(Color::Yellow)),
-                    Span::raw(&j.start_time),
-                ]);
+    /// The keymap of the shown tab. Returns whether the key was consumed.
+    fn handle_tab_key(&mut self, key: KeyEvent) -> bool {
+        // the matrix and the search take h/l themselves
+        let focus_keys = !(self.focus == Focus::Jobs && self.matrix.is_some()
+            || self.tab == Tab::History && self.history_tab.searching());
+        match key.code {
+            KeyCode::Char('h') | KeyCode::Left if focus_keys => {
+                self.move_focus(false);
+                return true;
             }
-            if let Some(s) = j.reason.as_deref() {
-                state_spans.extend([
+            KeyCode::Char('l') | KeyCode::Right if focus_keys => {
+                self.move_focus(true);
+                return true;
+            }
+            _ => {}
+        }
+        match self.tab {
+            Tab::Jobs => self.handle_jobs_key(key),
+            Tab::Nodes => self.nodes_tab.handle_key(key, self.focus),
+            Tab::Partitions => self.partitions_tab.handle_key(key),
+            Tab::Account => self.account_tab.handle_key(key, self.focus),
+            Tab::History => {
+                self.history_tab.handle_key(key, self.focus)
+                    || match key.code {
+                        KeyCode::Char('p') => {
+                            self.open_history_output(Viewer::Pager);
+                            true
+                        }
+                        KeyCode::Char('v') => {
+                            self.open_history_output(Viewer::Editor);
+                            true
+                        }
+                        KeyCode::Char('o') => {
+                            self.toggle_output_file_view();
+                            true
+                        }
+                        _ => false,
+                    }
+            }
+        }
+    }
+
+    /// Returns whether the key was consumed.
+    fn handle_jobs_key(&mut self, key: KeyEvent) -> bool {
+        let consumed = match self.focus {
+            Focus::Jobs => {
+                self.handle_matrix_key(key) || {
+                    let consumed = self.handle_job_list_key(key);
+                    if consumed {
+                        self.job_details_scroll.reset();
+                    }
+                    consumed
+                }
+            }
+            Focus::Details => self.job_details_scroll.handle_key(key),
+            Focus::Log => self.handle_log_key(key),
+            _ => false,
+        };
+        if consumed {
+            return true;
+        }
+        match key.code {
+            KeyCode::Home => {
+                self.job_output_offset = 0;
+                self.job_output_anchor = ScrollAnchor::Top;
+            }
+            KeyCode::End => {
+                self.job_output_offset = 0;
+                self.job_output_anchor = ScrollAnchor::Bottom;
+            }
+            KeyCode::Char('c') => {
+                // an array is signalled as a whole
+                if let Some(job_id) = self
+                    .selected_array()
+                    .map(String::from)
+                    .or_else(|| self.selected_job().map(|j| j.id()))
+                {
+                    self.dialog = Some(Dialog::SignalJob {
+                        job_id,
+                        form: Scancel::form(),
+                    });
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(job_id) = self.selected_job().map(|j| j.id()) {
+                    self.spawn_query({
+                        let job_id = job_id.clone();
+                        move || {
+                            let info = slurm::show_job(&job_id);
+                            AppMessage::JobInfo(job_id, info)
+                        }
+                    });
+                    self.dialog = Some(Dialog::EditJob { job_id, edit: None });
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(job) = self.selected_job() {
+                    let job_id = job.job_id.clone();
+                    // resubmit only this task, not the whole array
+                    let array_task = job
+                        .array_step
+                        .clone()
+                        .filter(|s| s.chars().all(|c| c.is_ascii_digit()));
+                    self.spawn_query({
+                        let job_id = job_id.clone();
+                        move || {
+                            let resubmit = Resubmit::fetch(&job_id, array_task.as_deref());
+                            AppMessage::Resubmit(job_id, resubmit)
+                        }
+                    });
+                    self.dialog = Some(Dialog::Resubmit {
+                        job_id,
+                        resubmit: None,
+                    });
+                }
+            }
+            KeyCode::Char('s') => {
+                if let Some(job_id) = self
+                    .selected_array()
+                    .map(String::from)
+                    .or_else(|| self.selected_job().map(|j| j.job_id.clone()))
+                {
+                    self.spawn_query({
+                        let job_id = job_id.clone();
+                        move || {
+                            let script = slurm::batch_script(&job_id);
+                            AppMessage::BatchScript(job_id, script)
+                        }
+                    });
+                    self.dialog = Some(Dialog::BatchScript {
+                        job_id,
+                        script: None,
+                        offset: 0,
+                    });
+                }
+            }
+            KeyCode::Char('a') => {
+                self.dialog = Some(Dialog::ActionHistory(0));
+            }
+            KeyCode::Char('t') => self.dialog = Some(Dialog::Totals),
+            KeyCode::Char('D') => {
+                if let Some(job_id) = self
+                    .selected_array()
+                    .map(|a| format!("{}_*", a))
+                    .or_else(|| self.selected_job().map(dependency::node_id))
+                {
+                    self.dialog = Some(Dialog::Dependencies { job_id, offset: 0 });
+                }
+            }
+            KeyCode::Char(' ') => {
+                let ids: Vec<String> = match self.selected_row() {
+                    // the task under the cursor, if it is still listed
+                    _ if self.matrix.is_some() => {
+                        self.selected_job().map(|j| j.id()).into_iter().collect()
+                    }
+                    Some(Row::Array { tasks, .. }) => {
+                        tasks.iter().map(|i| self.jobs[*i].id()).collect()
+                    }
+                    Some(Row::Job(i)) => vec![self.jobs[*i].id()],
+                    None => Vec::new(),
+                };
+                if !ids.is_empty() {
+                    // arrays are marked as a whole, unless all of their tasks already are
+                    if ids.iter().all(|id| self.marked.contains(id)) {
+                        for id in &ids {
+                            self.marked.remove(id);
+                        }
+                    } else {
+                        self.marked.extend(ids);
+                    }
+                    if self.matrix.is_none() {
+                        self.select_next_job();
+                    }
+                }
+            }
+            KeyCode::Esc if !self.marked.is_empty() => self.marked.clear(),
+            KeyCode::Char('i') => {
+                if let Some(job) = self.selected_job().filter(|j| j.state == JobState::Running) {
+                    let attach = self.attach_command.replace("{job_id}", &job.job_id);
+                    let mut command = Command::new("sh");
+                    command.arg("-c").arg(&attach);
+                    self.foreground = Some(Foreground {
+                        command,
+                        description: attach,
+                    });
+                }
+            }
+            KeyCode::Char('p') => self.open_output(Viewer::Pager),
+            KeyCode::Char('v') => self.open_output(Viewer::Editor),
+            KeyCode::Char('n') => {
+                self.dialog = Some(Dialog::PickTemplate {
+                    templates: Template::load_all().map_err(|e| {
+                        format!(
+                            "Failed to read {}: {}",
+                            template::templates_dir().display(),
+                            e
+                        )
+                    }),
+                    selected: 0,
+                });
+            }
+            KeyCode::Char('o') => self.toggle_output_file_view(),
+            KeyCode::Char('w') => {
+                self.job_output_wrap = !self.job_output_wrap;
+            }
+            KeyCode::Char('L') => self.show_remaining = !self.show_remaining,
+            KeyCode::Char('M') => {
+                let array = match self.selected_row() {
+                    Some(Row::Array {
+                        array_id, tasks, ..
+                    }) => Some((array_id.clone(), self.jobs[tasks[0]].array_step.clone())),
+                    _ => self
+                        .selected_job()
+                        .filter(|j| j.array_step.is_some())
+                        .map(|j| (j.array_id.clone(), j.array_step.clone())),
+                };
+                if let Some((array_id, step)) = array {
+                    let cursor = step
+                        .and_then(|s| array_matrix::parse_indices(&s).first().copied())
+                        .unwrap_or(0);
+                    self.matrix = Some(Matrix {
+                        array_id,
+                        cursor,
+                        accounted: None,
+                        since: Instant::now(),
+                    });
+                }
+            }
+            // the list is hidden behind the matrix
+            KeyCode::Char('T') | KeyCode::Enter if self.matrix.is_some() => {}
+            KeyCode::Char('T') => {
+                let selection = self.selection();
+                self.tree = !self.tree;
+                self.update_rows(selection);
+            }
+            KeyCode::Enter if self.tree => {
+                // expands or collapses the selected array, or the array of the selected task
+                let array_id = match self.selected_row() {
+                    Some(Row::Array { array_id, .. }) => Some(array_id.clone()),
+                    _ => self
+                        .selected_job()
+                        .filter(|j| j.array_step.is_some())
+                        .map(|j| j.array_id.clone()),
+                };
+                if let Some(array_id) = array_id {
+                    let selection = self.selection();
+                    if !self.expanded.remove(&array_id) {
+                        self.expanded.insert(array_id);
+                    }
+                    self.update_rows(selection);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn handle_dialog_key(&mut self, dialog: Dialog, key: KeyEvent) -> Option<Dialog> {
+        match dialog {
+            Dialog::SignalJob { job_id, mut form } => match form.handle_key(key) {
+                FormEvent::Submit => match Scancel::from_form(&job_id, &form) {
+                    Ok(scancel) => Some(Dialog::ConfirmSignal(scancel)),
+                    Err(e) => {
+                        form.error = Some(e);
+                        Some(Dialog::SignalJob { job_id, form })
+                    }
+                },
+                FormEvent::Cancel => None,
+                FormEvent::None => Some(Dialog::SignalJob { job_id, form }),
+            },
+            Dialog::ConfirmSignal(scancel) => match key.code {
+                KeyCode::Enter | KeyCode::Char('y') => {
+                    self.run_action(Action::Scancel(scancel));
+                    None
+                }
+                KeyCode::Esc => None,
+                _ => Some(Dialog::ConfirmSignal(scancel)),
+            },
+            Dialog::EditJob {
+                job_id,
+                edit: Some(Ok(mut edit)),
+            } => match edit.form.handle_key(key) {
+                FormEvent::Submit => match edit.update() {
+                    Ok(update) => {
+                        self.run_action(Action::Update(update));
+                        None
+                    }
+                    Err(e) => {
+                        edit.form.error = Some(e);
+                        Some(Dialog::EditJob {
+                            job_id,
+                            edit: Some(Ok(edit)),
+                        })
+                    }
+                },
+                FormEvent::Cancel => None,
+                FormEvent::None => Some(Dialog::EditJob {
+                    job_id,
+                    edit: Some(Ok(edit)),
+                }),
+            },
+            Dialog::EditJob { job_id, edit } => match key.code {
+                KeyCode::Esc | KeyCode::Enter => None,
+                _ => Some(Dialog::EditJob { job_id, edit }),
+            },
+            Dialog::Resubmit {
+                job_id,
+                resubmit: Some(Ok(mut resubmit)),
+            } => match resubmit.form.handle_key(key) {
+                FormEvent::Submit => match resubmit.submission() {
+                    Ok(submission) => {
+                        self.run_action(Action::Submit(submission));
+                        None
+                    }
+                    Err(e) => {
+                        resubmit.form.error = Some(e);
+                        Some(Dialog::Resubmit {
+                            job_id,
+                            resubmit: Some(Ok(resubmit)),
+                        })
+                    }
+                },
+                FormEvent::Cancel => None,
+                FormEvent::None => Some(Dialog::Resubmit {
+                    job_id,
+                    resubmit: Some(Ok(resubmit)),
+                }),
+            },
+            Dialog::Resubmit { job_id, resubmit } => match key.code {
+                KeyCode::Esc | KeyCode::Enter => None,
+                _ => Some(Dialog::Resubmit { job_id, resubmit }),
+            },
+            Dialog::BatchScript {
+                job_id,
+                script,
+                offset,
+            } => {
+                let offset = match key.code {
+                    KeyCode::Esc | KeyCode::Char('s') => return None,
+                    _ => self.scroll_dialog(offset, key).unwrap_or(offset),
+                };
+                Some(Dialog::BatchScript {
+                    job_id,
+                    script,
+                    offset,
+                })
+            }
+            Dialog::ActionHistory(offset) => match key.code {
+                KeyCode::Esc | KeyCode::Char('a') => None,
+                _ => Some(Dialog::ActionHistory(
+                    self.scroll_dialog(offset, key).unwrap_or(offset),
+                )),
+            },
+            Dialog::Totals => match key.code {
+                KeyCode::Esc | KeyCode::Char('t') => None,
+                _ => Some(Dialog::Totals),
+            },
+            Dialog::Dependencies { job_id, offset } => match key.code {
+                KeyCode::Esc | KeyCode::Char('D') => None,
+                _ => Some(Dialog::Dependencies {
+                    job_id,
+                    offset: self.scroll_dialog(offset, key).unwrap_or(offset),
+                }),
+            },
+            Dialog::PickTemplate {
+                templates,
+                selected,
+            } => {
+                let count = templates.as_ref().map_or(0, |t| t.len());
+                let selected = match key.code {
+                    KeyCode::Esc | KeyCode::Char('n') => return None,
+                    KeyCode::Char('j') | KeyCode::Down => {
+                        (selected + 1).min(count.saturating_sub(1))
+                    }
+                    KeyCode::Char('k') | KeyCode::Up => selected.saturating_sub(1),
+                    KeyCode::Enter => {
+                        let template = match templates {
+                            Ok(mut templates) if selected < templates.len() => {
+                                templates.swap_remove(selected)
+                            }
+                            _ => return None,
+                        };
+                        let form = template.form();
+                        if form.fields.is_empty() {
+                            let script = template.content.clone();
+                            return Some(self.preview_submission(template.submission(script)));
+                        }
+                        return Some(Dialog::TemplateVariables { template, form });
+                    }
+                    _ => selected,
+                };
+                Some(Dialog::PickTemplate {
+                    templates,
+                    selected,
+                })
+            }
+            Dialog::TemplateVariables { template, mut form } => match form.handle_key(key) {
+                FormEvent::Submit => match template.render_form(&form) {
+                    Ok(script) => Some(self.preview_submission(template.submission(script))),
+                    Err(e) => {
+                        form.error = Some(e);
+                        Some(Dialog::TemplateVariables { template, form })
+                    }
+                },
+                FormEvent::Cancel => None,
+                FormEvent::None => Some(Dialog::TemplateVariables { template, form }),
+            },
+            Dialog::SubmitPreview {
+                submission,
+                estimate,
+                offset,
+            } => {
+                let offset = match key.code {
+                    // only once sbatch accepted the script
+                    KeyCode::Enter | KeyCode::Char('y') if matches!(estimate, Some(Ok(_))) => {
+                        self.run_action(Action::Submit(submission));
+                        return None;
+                    }
+                    KeyCode::Esc => return None,
+                    _ => self.scroll_dialog(offset, key).unwrap_or(offset),
+                };
+                Some(Dialog::SubmitPreview {
+                    submission,
+                    estimate,
+                    offset,
+                })
+            }
+        }
+    }
+
+    /// The offset of a scrollable dialog after a scroll key, `None` for other keys.
+    /// The last line stays at the bottom, see `dialog_lines` and `dialog_height`.
+    fn scroll_dialog(&self, offset: u16, key: KeyEvent) -> Option<u16> {
+        scroll::scroll(offset, key, self.dialog_lines, self.dialog_height)
+    }
+
+    /// Whether keys are needed for text input, so that `q` must not quit.
+    fn captures_input(&self) -> bool {
+        self.dialog.is_some() || (self.tab == Tab::History && self.history_tab.searching())
+    }
+
+    /// Only the shown tab's watcher runs, to spare the Slurm controller.
+    fn select_tab(&mut self, tab: Tab) {
+        if tab == self.tab {
+            return;
+        }
+        match self.tab {
+            Tab::Nodes => self.nodes_tab.deactivate(),
+            Tab::Partitions => self.partitions_tab.deactivate(),
+            Tab::Account => self.account_tab.deactivate(),
+            Tab::Jobs | Tab::History => {}
+        }
+        match tab {
+            Tab::Nodes => {
+                self.nodes_tab
+                    .activate(self.sender.clone(), self.node_refresh);
+                self.update_node_jobs();
+            }
+            Tab::Partitions => self
+                .partitions_tab
+                .activate(self.sender.clone(), self.node_refresh),
+            Tab::Account => self
+                .account_tab
+                .activate(self.sender.clone(), self.account_refresh),
+            Tab::History => self.history.request(),
+            Tab::Jobs => {}
+        }
+        self.tab = tab;
+        self.focus = Focus::panels(tab)[0];
+    }
+
+    fn toggle_output_file_view(&mut self) {
+        self.output_file_view = match self.output_file_view {
+            OutputFileView::Stdout => OutputFileView::Stderr,
+            OutputFileView::Stderr => OutputFileView::Stdout,
+        };
+    }
+
+    fn open_history_output(&mut self, viewer: Viewer) {
+        let Some(path) = self
+            .history_tab
+            .selected()
+            .and_then(|e| match self.output_file_view {
+                OutputFileView::Stdout => e.stdout.clone(),
+                OutputFileView::Stderr => e.stderr.clone(),
+            })
+        else {
+            return;
+        };
+        let program = pager::program(viewer);
+        self.foreground = Some(Foreground {
+            command: pager::command(&program, &path, Position::Top(1)),
+            description: format!("{} {}", program, path.display()),
+        });
+    }
+
+    /// The output file of the selected job, or of the task under the cursor of the matrix
+    /// that is only in accounting.
+    fn selected_output_path(&self) -> Option<PathBuf> {
+        let (stdout, stderr) = match self.selected_job() {
+            Some(job) => (&job.stdout, &job.stderr),
+            None => {
+                let matrix = self.matrix.as_ref()?;
+                let Some(Ok(tasks)) = &matrix.accounted else {
+                    return None;
+                };
+                let task = tasks.iter().find(|t| t.index == matrix.cursor)?;
+                (&task.stdout, &task.stderr)
+            }
+        };
+        match self.output_file_view {
+            OutputFileView::Stdout => stdout.clone(),
+            OutputFileView::Stderr => stderr.clone(),
+        }
+    }
+
+    /// Opens the shown output file at the current scroll position.
+    fn open_output(&mut self, viewer: Viewer) {
+        let Some(path) = self.selected_output_path() else {
+            return;
+        };
+        let position = Position::from_view(
+            self.job_output.as_deref().unwrap_or_default(),
+            matches!(self.job_output_anchor, ScrollAnchor::Bottom),
+            self.job_output_offset as usize,
+        );
+        let program = pager::program(viewer);
+        self.foreground = Some(Foreground {
+            command: pager::command(&program, &path, position),
+            description: format!("{} {}", program, path.display()),
+        });
+    }
+
+    /// Shows the submission and estimates its start in the background.
+    fn preview_submission(&self, submission: Submission) -> Dialog {
+        self.spawn_query({
+            let submission = submission.clone();
+            move || {
+                let estimate = submit::test_only(&submission);
+                AppMessage::TestOnly(submission.script, estimate)
+            }
+        });
+        Dialog::SubmitPreview {
+            submission,
+            estimate: None,
+            offset: 0,
+        }
+    }
+
+    fn ui(&mut self, f: &mut Frame) {
+        // Layout
+
+        let tabs_content_help = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(1),
+                    Constraint::Min(3),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(f.area());
+        let content = tabs_content_help[1];
+
+        // Tabs
+        let tabs = Tabs::new(TABS.iter().enumerate().map(|(i, tab)| {
+            Line::from(vec![
+                Span::styled(format!("{} ", i + 1), Style::default().fg(Color::Blue)),
+                Span::raw(tab.title()),
+            ])
+        }))
+        .select(TABS.iter().position(|t| *t == self.tab))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_widget(tabs, tabs_content_help[0]);
+
+        // Help
+        let help_options = match self.tab {
+            Tab::Jobs => vec![
+                ("⏴/⏵", "panel"),
+                ("⏶/⏷", "navigate"),
+                ("pgup/pgdown", "scroll"),
+                ("home/end", "top/bottom"),
+                ("esc", "cancel"),
+                ("enter", "confirm"),
+                ("c", "cancel/signal job"),
+                ("e", "edit job"),
+                ("r", "resubmit job"),
+                ("s", "batch script"),
+                ("a", "action history"),
+                ("space", "mark job"),
+                ("t", "totals"),
+                ("D", "dependencies"),
+                ("n", "submit from template"),
+                ("i", "shell in job"),
+                ("p/v", "open log in pager/editor"),
+                ("o", "toggle stdout/stderr"),
+                ("w", "toggle text wrap"),
+                ("L", "toggle time left"),
+                ("T", "group arrays"),
+                ("M", "array task matrix"),
+                ("enter", "expand/collapse array"),
+            ],
+            Tab::Nodes => NodesTab::help(),
+            Tab::Partitions => PartitionsTab::help(),
+            Tab::Account => AccountTab::help(),
+            Tab::History => HistoryTab::help(),
+        };
+        let help_options = [("q", "quit"), ("1-5/tab", "switch tab")]
+            .into_iter()
+            .chain(help_options);
+        let blue_style = Style::default().fg(Color::Blue);
+        let light_blue_style = Style::default().fg(Color::LightBlue);
+
+        let help = Line::from(
+            help_options.fold(Vec::new(), |mut acc, (key, description)| {
+                if !acc.is_empty() {
+                    acc.push(Span::raw(" | "));
+                }
+                acc.push(Span::styled(key, blue_style));
+                acc.push(Span::raw(": "));
+                acc.push(Span::styled(description, light_blue_style));
+                acc
+            }),
+        );
+
+        let help = Paragraph::new(help);
+        f.render_widget(help, tabs_content_help[2]);
+
+        // no panel has the focus while a dialog is open
+        let focus = self.dialog.is_none().then_some(self.focus);
+        // the jobs still in the queue
+        let queued: Vec<&Job> = self
+            .jobs
+            .iter()
+            .filter(|j| !self.vanished.contains_key(&j.id()))
+            .collect();
+        match self.tab {
+            Tab::Jobs => self.render_jobs_tab(f, content),
+            Tab::Nodes => self.nodes_tab.render(f, content, focus),
+            Tab::Partitions => self.partitions_tab.render(f, content, focus, &queued),
+            Tab::Account => self.account_tab.render(f, content, focus, &queued),
+            Tab::History => self.history_tab.render(
+                f,
+                content,
+                focus,
+                self.efficiency.as_ref().map(|i| i.report.as_ref()),
+            ),
+        }
+
+        if let Some(dialog) = &self.dialog {
+            match dialog {
+                Dialog::SignalJob { job_id, form } => {
+                    let mut lines = form.lines();
+                    lines.push(Line::default());
+                    lines.push(Line::styled(
+                        "tab/⏶/⏷: field | ⏴/⏵: choose | enter: continue | esc: abort",
+                        Style::default().add_modifier(Modifier::DIM),
+                    ));
+                    let dialog = Paragraph::new(lines)
+                        .style(Style::default().fg(Color::White))
+                        .block(
+                            Block::default()
+                                .title(format!("─Signal job {}", job_id))
+                                .borders(Borders::ALL)
+                                .border_type(BorderType::Rounded)
+                                .border_style(Style::default().fg(Color::Green)),
+                        );
+
+                    let area = centered_lines(75, form.height() + 4, f.area());
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::ConfirmSignal(scancel) => {
+                    let dialog = Paragraph::new(Line::from(vec![
+                        Span::styled(
+                            scancel.description(),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        Span::raw("?"),
+                    ]))
+                    .style(Style::default().fg(Color::White))
+                    .wrap(Wrap { trim: true })
+                    .block(
+                        Block::default()
+                            .title("─Confirm")
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .style(Style::default().fg(Color::Green)),
+                    );
+
+                    let area = centered_lines(75, 3, f.area());
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::EditJob { job_id, edit } => render_form_dialog(
+                    f,
+                    format!("─Edit job {}", job_id),
+                    edit.as_ref().map(|e| e.as_ref().map(|e| &e.form)),
+                    "tab/⏶/⏷: field | enter: apply | esc: abort",
+                ),
+                Dialog::Resubmit { job_id, resubmit } => render_form_dialog(
+                    f,
+                    format!("─Resubmit job {}", job_id),
+                    resubmit.as_ref().map(|r| r.as_ref().map(|r| &r.form)),
+                    "tab/⏶/⏷: field | enter: submit | esc: abort",
+                ),
+                Dialog::BatchScript {
+                    job_id,
+                    script,
+                    offset,
+                } => {
+                    let lines = match script {
+                        None => vec![Line::styled(
+                            "Loading batch script…",
+                            Style::default().add_modifier(Modifier::DIM),
+                        )],
+                        Some(Err(e)) => {
+                            vec![Line::styled(e.as_str(), Style::default().fg(Color::Red))]
+                        }
+                        Some(Ok(s)) => script_lines(s),
+                    };
+                    self.dialog_lines = lines.len() as u16;
+                    let dialog = Paragraph::new(lines)
+                        .scroll((*offset, 0))
+                        .style(Style::default().fg(Color::White))
+                        .block(
+                            Block::default()
+                                .title(format!("─Batch script of job {}", job_id))
+                                .borders(Borders::ALL)
+                                .border_type(BorderType::Rounded)
+                                .border_style(Style::default().fg(Color::Green)),
+                        );
+
+                    let area = centered_lines(90, f.area().height.saturating_sub(4), f.area());
+                    self.dialog_height = area.height.saturating_sub(2);
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::ActionHistory(offset) => {
+                    let now = Instant::now();
+                    let lines: Vec<Line> = self
+                        .actions
+                        .iter()
+                        .rev()
+                        .flat_map(|entry| {
+                            let mut line = toast_line(entry);
+                            line.spans.insert(
+                                0,
+                                Span::styled(
+                                    format!(
+                                        "{:>4} ",
+                                        format_elapsed(now.duration_since(entry.queued_at))
+                                    ),
+                                    Style::default().add_modifier(Modifier::DIM),
+                                ),
+                            );
+                            let output = match &entry.status {
+                                ActionStatus::Succeeded(output) => output.as_str(),
+                                ActionStatus::Failed(e) => {
+                                    e.split_once('\n').map_or("", |(_, e)| e)
+                                }
+                                _ => "",
+                            };
+                            once(line).chain(output.lines().map(|l| {
+                                Line::styled(
+                                    format!("       {}", l),
+                                    Style::default().add_modifier(Modifier::DIM),
+                                )
+                            }))
+                        })
+                        .collect();
+                    let lines = if lines.is_empty() {
+                        vec![Line::styled(
+                            "No actions yet",
+                            Style::default().add_modifier(Modifier::DIM),
+                        )]
+                    } else {
+                        lines
+                    };
+                    self.dialog_lines = lines.len() as u16;
+                    let dialog = Paragraph::new(lines).scroll((*offset, 0)).block(
+                        Block::default()
+                            .title(format!("─Action history ({})", self.actions.len()))
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .border_style(Style::default().fg(Color::Green)),
+                    );
+
+                    let area = centered_lines(80, f.area().height * 2 / 3, f.area());
+                    self.dialog_height = area.height.saturating_sub(2);
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::Totals => {
+                    let queued: Vec<&Job> = self
+                        .jobs
+                        .iter()
+                        .filter(|j| !self.vanished.contains_key(&j.id()))
+                        .collect();
+                    let bold = Style::default().add_modifier(Modifier::BOLD);
+                    let mut lines = Vec::new();
+                    for (title, groups) in [
+                        ("User", totals(&queued, |j| j.user.as_str())),
+                        ("Partition", totals(&queued, |j| j.partition.as_str())),
+                    ] {
+                        let width = groups
+                            .iter()
+                            .map(|(key, ..)| key.len())
+                            .chain([title.len()])
+                            .max()
+                            .unwrap_or(0);
+                        if !lines.is_empty() {
+                            lines.push(Line::default());
+                        }
+                        lines.push(Line::styled(
+                            format!(
+                                "{:<width$} {:>7} {:>7} {:>6} {:>7} {:>5}",
+                                title, "Running", "Pending", "CPUs", "Memory", "GPUs"
+                            ),
+                            bold,
+                        ));
+                        for (key, running, pending, tres) in groups {
+                            lines.push(Line::raw(format!(
+                                "{:<width$} {:>7} {:>7} {:>6} {:>7} {:>5}",
+                                key,
+                                running,
+                                pending,
+                                tres.cpu,
+                                slurm::format_size(tres.mem),
+                                tres.gpus()
+                            )));
+                        }
+                    }
+                    let height = lines.len() as u16 + 2;
+                    let dialog = Paragraph::new(lines).block(
+                        Block::default()
+                            .title("─Totals (resources of running jobs)")
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .border_style(Style::default().fg(Color::Green)),
+                    );
+
+                    let area = centered_lines(60, height, f.area());
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::Dependencies { job_id, offset } => {
+                    let edges = dependency::edges(&self.jobs);
+                    let blocked: HashSet<String> = self
+                        .jobs
+                        .iter()
+                        .filter(|j| j.reason.as_deref() == Some("DependencyNeverSatisfied"))
+                        .map(dependency::node_id)
+                        .collect();
+                    let bold = Style::default().add_modifier(Modifier::BOLD);
+                    let never_satisfied = Span::styled(
+                        " ✗ never satisfied",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    );
+                    let tree_lines = |direction| {
+                        dependency::tree(&edges, job_id, direction, &blocked)
+                            .into_iter()
+                            .map(|line| {
+                                let mut spans = vec![
+                                    Span::raw(line.prefix),
+                                    Span::styled(
+                                        format!("{} ", line.dependency.kind),
+                                        Style::default().add_modifier(Modifier::DIM),
+                                    ),
+                                ];
+                                spans.extend(self.dependency_node_spans(line.id));
+                                if !line.dependency.status.is_empty() {
+                                    spans.push(Span::styled(
+                                        format!(" ({})", line.dependency.status),
+                                        Style::default().add_modifier(Modifier::DIM),
+                                    ));
+                                }
+                                if line.repeated {
+                                    spans.push(Span::styled(
+                                        " (see above)",
+                                        Style::default().add_modifier(Modifier::DIM),
+                                    ));
+                                }
+                                if line.never_satisfied {
+                                    spans.push(never_satisfied.clone());
+                                }
+                                Line::from(spans)
+                            })
+                            .collect::<Vec<_>>()
+                    };
+                    let ancestors = tree_lines(dependency::Direction::Ancestors);
+                    let descendants = tree_lines(dependency::Direction::Descendants);
+
+                    let mut root = vec![Span::raw("▶ ")];
+                    root.extend(self.dependency_node_spans(job_id));
+                    if blocked.contains(job_id) {
+                        root.push(never_satisfied.clone());
+                    }
+                    let mut lines = vec![Line::styled("Waits for", bold)];
+                    if ancestors.is_empty() {
+                        lines.push(Line::styled(
+                            "Nothing among the listed jobs",
+                            Style::default().add_modifier(Modifier::DIM),
+                        ));
+                    }
+                    lines.extend(ancestors);
+                    lines.extend([Line::default(), Line::from(root), Line::default()]);
+                    lines.push(Line::styled("Needed by", bold));
+                    if descendants.is_empty() {
+                        lines.push(Line::styled(
+                            "No listed job",
+                            Style::default().add_modifier(Modifier::DIM),
+                        ));
+                    }
+                    lines.extend(descendants);
+
+                    let height = (lines.len() as u16 + 2).min(f.area().height * 2 / 3);
+                    self.dialog_lines = lines.len() as u16;
+                    let dialog = Paragraph::new(lines).scroll((*offset, 0)).block(
+                        Block::default()
+                            .title(format!("─Dependencies of {}", job_id))
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .border_style(Style::default().fg(Color::Green)),
+                    );
+
+                    let area = centered_lines(80, height, f.area());
+                    self.dialog_height = area.height.saturating_sub(2);
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::PickTemplate {
+                    templates,
+                    selected,
+                } => {
+                    let dim = Style::default().add_modifier(Modifier::DIM);
+                    let mut lines = match templates {
+                        Err(e) => vec![Line::styled(e.as_str(), Style::default().fg(Color::Red))],
+                        Ok(templates) if templates.is_empty() => vec![Line::styled(
+                            format!(
+                                "No templates found in {}",
+                                template::templates_dir().display()
+                            ),
+                            dim,
+                        )],
+                        Ok(templates) => templates
+                            .iter()
+                            .enumerate()
+                            .map(|(i, t)| {
+                                if i == *selected {
+                                    Line::styled(
+                                        t.name.as_str(),
+                                        Style::default().bg(Color::Green).fg(Color::Black),
+                                    )
+                                } else {
+                                    Line::raw(t.name.as_str())
+                                }
+                            })
+                            .collect(),
+                    };
+                    lines.push(Line::default());
+                    lines.push(Line::styled(
+                        "⏶/⏷: select | enter: choose | esc: abort",
+                        dim,
+                    ));
+                    let height = lines.len() as u16 + 2;
+                    let dialog = Paragraph::new(lines)
+                        .style(Style::default().fg(Color::White))
+                        .block(
+                            Block::default()
+                                .title("─Submit from template")
+                                .borders(Borders::ALL)
+                                .border_type(BorderType::Rounded)
+                                .border_style(Style::default().fg(Color::Green)),
+                        );
+
+                    let area = centered_lines(75, height, f.area());
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+                Dialog::TemplateVariables { template, form } => render_form_dialog(
+                    f,
+                    format!("─Submit {}", template.name),
+                    Some(Ok(form)),
+                    "tab/⏶/⏷: field | enter: preview | esc: abort",
+                ),
+                Dialog::SubmitPreview {
+                    submission,
+                    estimate,
+                    offset,
+                } => {
+                    let dim = Style::default().add_modifier(Modifier::DIM);
+                    let mut lines = vec![
+                        match estimate {
+                            None => Line::styled("Estimating start time…", dim),
+                            Some(Ok(estimate)) => {
+                                Line::styled(estimate.as_str(), Style::default().fg(Color::Green))
+                            }
+                            Some(Err(e)) => {
+                                Line::styled(e.as_str(), Style::default().fg(Color::Red))
+                            }
+                        },
+                        Line::default(),
+                    ];
+                    lines.extend(script_lines(&submission.script));
+                    self.dialog_lines = lines.len() as u16;
+                    let hint = match estimate {
+                        None => "─estimating… | ⏶/⏷: scroll | esc: abort",
+                        Some(Ok(_)) => "─enter/y: submit | ⏶/⏷: scroll | esc: abort",
+                        Some(Err(_)) => "─rejected by sbatch | ⏶/⏷: scroll | esc: abort",
+                    };
+                    let dialog = Paragraph::new(lines)
+                        .scroll((*offset, 0))
+                        .style(Style::default().fg(Color::White))
+                        .block(
+                            Block::default()
+                                .title(format!("─{}", submission.description))
+                                .title_bottom(Line::styled(hint, dim))
+                                .borders(Borders::ALL)
+                                .border_type(BorderType::Rounded)
+                                .border_style(Style::default().fg(Color::Green)),
+                        );
+
+                    let area = centered_lines(90, f.area().height.saturating_sub(4), f.area());
+                    self.dialog_height = area.height.saturating_sub(2);
+                    f.render_widget(Clear, area);
+                    f.render_widget(dialog, area);
+                }
+            }
+        }
+
+        // Toasts, the latest ones
+        let mut toasts: Vec<Line> = self
+            .toasts
+            .iter()
+            .filter_map(|t| self.actions.iter().find(|a| a.id == t.action_id))
+            .map(toast_line)
+            .collect();
+        if toasts.len() > MAX_TOASTS {
+            let more = toasts.len() - MAX_TOASTS;
+            toasts.drain(..more);
+            toasts.insert(
+                0,
+                Line::styled(
+                    format!("+{} more", more),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            );
+        }
+        if !toasts.is_empty() {
+            let width = toasts
+                .iter()
+                .map(|l| l.width() as u16 + 2)
+                .max()
+                .unwrap_or(0)
+                .min(content.width.min(80));
+            let height = (toasts.len() as u16 + 2).min(content.height);
+            let area = Rect::new(
+                content.right().saturating_sub(width + 1),
+                content.bottom().saturating_sub(height + 1),
+                width,
+                height,
+            );
+            let toasts = Paragraph::new(toasts).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded),
+            );
+            f.render_widget(Clear, area);
+            f.render_widget(toasts, area);
+        }
+    }
+
+    fn render_jobs_tab(&mut self, f: &mut Frame, area: Rect) {
+        let master_detail = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(50), Constraint::Percentage(70)].as_ref())
+            .split(area);
+
+        // Job details
+
+        let job_detail = self.selected_job_index().and_then(|i| self.jobs.get(i));
+        let job_detail = job_detail.map(|j| {
+            let mut state_spans = vec![
+                Span::styled("State  ", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::styled(j.state.as_str(), self.theme.state(&j.state)),
+            ];
+            if let Some(vanished) = self.vanished.get(&j.id()) {
+                state_spans.extend([
+                    Span::styled(
+                        format!(
+                            " (left queue {} ago)",
+                            format_elapsed(vanished.since.elapsed())
+                        ),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                    Span::styled(" Final ", Style::default().fg(Color::Yellow)),
+                    match &vanished.final_state {
+                        None => Span::styled("…", Style::default().add_modifier(Modifier::DIM)),
+                        Some(Ok(state)) => Span::raw(state.as_str()),
+                        Some(Err(e)) => Span::styled(e.as_str(), Style::default().fg(Color::Red)),
+                    },
+                ]);
+            }
+            if let Some(s) = j.reason.as_deref() {
+                state_spans.extend([
                     Span::styled(" Reason ", Style::default().fg(Color::Yellow)),
                     Span::raw(s),
                 ]);
             }
             let state = Line::from(state_spans);
 
+            // start and end are estimates until they passed
+            let mut time_spans = vec![
+                Span::styled("Time   ", Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::raw(j.time.to_string()),
+            ];
+            for (label, time) in [
+                (" Submit ", j.submit_time),
+                (" Start ", j.start_time),
+                (" End ", j.end_time),
+            ] {
+                if let Some(time) = time {
+                    time_spans.extend([
+                        Span::styled(label, Style::default().fg(Color::Yellow)),
+                        Span::raw(time.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ]);
+                }
+            }
+            let times = Line::from(time_spans);
+
+            let limit = j.time_limit.map(|limit| {
+                let mut spans = vec![
+                    Span::styled("Limit  ", Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                ];
+                match j
+                    .remaining()
+                    .filter(|_| !self.vanished.contains_key(&j.id()))
+                {
+                    Some(remaining) => {
+                        let fraction = j.time.0.as_secs_f64() / limit.0.as_secs_f64().max(1.0);
+                        let color = if remaining < self.limit_margin {
+                            Color::Red
+                        } else {
+                            Color::Green
+                        };
+                        spans.extend([
+                            Span::styled(progress_bar(fraction, 20), Style::default().fg(color)),
+                            Span::raw(format!(
+                                " {} of {}, {} left",
+                                j.time,
+                                limit,
+                                SlurmDuration(remaining)
+                            )),
+                        ]);
+                    }
+                    None => spans.push(Span::raw(limit.to_string())),
+                }
+                Line::from(spans)
+            });
+
             let command = Line::from(vec![
                 Span::styled("Command", Style::default().fg(Color::Yellow)),
                 Span::raw(" "),
@@ -476,11 +2236,6 @@ impl App {
                 Span::raw(" "),
                 Span::raw(&j.nodelist),
             ]);
-            let tres = Line::from(vec![
-                Span::styled("TRES   ", Style::default().fg(Color::Yellow)),
-                Span::raw(" "),
-                Span::raw(&j.tres),
-            ]);
             let ui_stdout_text = match self.output_file_view {
                 OutputFileView::Stdout => "stdout ",
                 OutputFileView::Stderr => "stderr ",
@@ -497,15 +2252,340 @@ impl App {
                     .map(|p| p.to_str().unwrap_or_default())
                     .unwrap_or_default(),
                 ),
-            ]);
+            ]);
+
+            let mut lines = vec![state, times];
+            lines.extend(limit);
+            lines.extend([command, nodes]);
+            lines.extend(tres_lines(&j.tres));
+            if !j.dependencies.is_empty() {
+                let mut spans = vec![
+                    Span::styled("Deps   ", Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                ];
+                for (i, d) in j.dependencies.iter().enumerate() {
+                    if i > 0 {
+                        spans.push(Span::raw(", "));
+                    }
+                    spans.push(Span::styled(
+                        format!("{}:{}", d.kind, d.job_id),
+                        if d.never_satisfied() {
+                            Style::default().fg(Color::Red)
+                        } else {
+                            Style::default()
+                        },
+                    ));
+                    if !d.status.is_empty() {
+                        spans.push(Span::styled(
+                            format!(" ({})", d.status),
+                            Style::default().add_modifier(Modifier::DIM),
+                        ));
+                    }
+                }
+                lines.push(Line::from(spans));
+            }
+            lines.push(stdout);
+            if let Some(explanation) = j
+                .reason
+                .as_deref()
+                .filter(|_| j.state == JobState::Pending)
+                .and_then(pending::explain)
+            {
+                lines.push(Line::from(vec![
+                    Span::styled("Why    ", Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                    Span::raw(explanation.meaning),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Advice ", Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                    Span::raw(explanation.advice),
+                ]));
+            }
+            if let Some(info) = self.pending_info.as_ref().filter(|i| i.id == j.id()) {
+                let loading = Span::styled("…", Style::default().add_modifier(Modifier::DIM));
+                let error = |e: &String| Span::styled(e.clone(), Style::default().fg(Color::Red));
+                match &info.context {
+                    Some(Ok(None)) => {}
+                    context => lines.push(Line::from(vec![
+                        Span::styled("Context", Style::default().fg(Color::Yellow)),
+                        Span::raw(" "),
+                        match context {
+                            Some(Ok(context)) => Span::raw(context.clone().unwrap_or_default()),
+                            Some(Err(e)) => error(e),
+                            None => loading.clone(),
+                        },
+                    ])),
+                }
+                let mut priority = vec![
+                    Span::styled("Prio   ", Style::default().fg(Color::Yellow)),
+                    Span::raw(" "),
+                ];
+                match &info.priority {
+                    None => priority.push(loading),
+                    Some(Err(e)) => priority.push(error(e)),
+                    Some(Ok(p)) => {
+                        priority.push(Span::raw(p.priority.clone()));
+                        if let Some((position, pending)) = p.rank {
+                            priority.push(Span::raw(format!(
+                                " (#{} of {} pending in {})",
+                                position, pending, j.partition
+                            )));
+                        }
+                        for (name, value) in p.factors.iter().filter(|(_, v)| !v.is_empty()) {
+                            priority.extend([
+                                Span::styled(
+                                    format!(" {} ", name),
+                                    Style::default().fg(Color::Yellow),
+                                ),
+                                Span::raw(value.clone()),
+                            ]);
+                        }
+                    }
+                }
+                lines.push(Line::from(priority));
+            }
+            if let Some(usage) = self.usage.as_ref().filter(|u| u.job_id == j.job_id) {
+                lines.extend(usage_lines(usage));
+            }
+            if let Some(info) = self
+                .efficiency
+                .as_ref()
+                .filter(|i| i.id == j.id() && self.vanished.contains_key(&i.id))
+            {
+                lines.extend(efficiency_lines(info.report.as_ref(), "Used   ", "Advice "));
+            }
+            Text::from(lines)
+        });
+        let job_detail = job_detail
+            .or_else(|| self.matrix_detail())
+            .or_else(|| self.array_detail());
+        let job_detail_log = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(job_detail.as_ref().map_or(5, |t| t.height() as u16) + 2),
+                    Constraint::Min(3),
+                ]
+                .as_ref(),
+            )
+            .split(master_detail[1]);
+
+        // Jobs
+        let max_id_len = self
+            .rows
+            .iter()
+            .map(|r| r.id(&self.jobs).len())
+            .max()
+            .unwrap_or(0);
+        let max_user_len = self.jobs.iter().map(|j| j.user.len()).max().unwrap_or(0);
+        let max_partition_len = self
+            .jobs
+            .iter()
+            .map(|j| j.partition.len())
+            .max()
+            .unwrap_or(0);
+        let max_time_len = self
+            .jobs
+            .iter()
+            .map(|j| j.time.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let gpus = |j: &Job| match j.tres.gpus() {
+            0 => String::new(),
+            n => format!("{} GPU", n),
+        };
+        let max_gpus_len = self.jobs.iter().map(|j| gpus(j).len()).max().unwrap_or(0);
+        let remaining = |j: &Job| match j.remaining() {
+            Some(remaining) if self.show_remaining => format!("{} left", SlurmDuration(remaining)),
+            _ => String::new(),
+        };
+        let max_remaining_len = self
+            .jobs
+            .iter()
+            .map(|j| remaining(j).len())
+            .max()
+            .unwrap_or(0);
+        let states: Vec<(JobState, String)> =
+            self.jobs.iter().map(|j| self.shown_state(j)).collect();
+        let max_state_compact_len = states
+            .iter()
+            .map(|(_, compact)| compact.len())
+            .max()
+            .unwrap_or(0);
+        // in tree mode, arrays are flagged as collapsed or expanded in front
+        let tree = self.tree && self.rows.iter().any(|r| matches!(r, Row::Array { .. }));
+        let job_item = |i: usize| {
+            let j = &self.jobs[i];
+            let (state, state_compact) = &states[i];
+            // failed jobs are colored as a whole, jobs that left the queue are greyed out
+            let row = self.theme.row(state);
+            let vanished = self.vanished.contains_key(&j.id());
+            // running jobs close to their time limit are red
+            let near_limit = j.remaining().is_some_and(|r| r < self.limit_margin);
+            let style = |style: Style| {
+                if let Some(row) = row {
+                    row
+                } else if vanished {
+                    Style::default().fg(Color::DarkGray)
+                } else if near_limit {
+                    style.fg(Color::LightRed)
+                } else {
+                    style
+                }
+            };
+            // marked jobs are flagged in front, once any are
+            let mark = match (self.marked.is_empty(), self.marked.contains(&j.id())) {
+                (true, _) => Span::raw(""),
+                (false, true) => Span::styled("▌", Style::default().fg(Color::Magenta)),
+                (false, false) => Span::raw(" "),
+            };
+            ListItem::new(Line::from(vec![
+                mark,
+                Span::raw(if tree { "  " } else { "" }),
+                Span::styled(
+                    format!("{:<max$.max$}", state_compact, max = max_state_compact_len),
+                    row.unwrap_or(self.theme.state(state)),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{:<max$.max$}", j.id(), max = max_id_len),
+                    style(Style::default().fg(Color::Yellow)),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{:<max$.max$}", j.partition, max = max_partition_len),
+                    style(Style::default().fg(Color::Blue)),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{:<max$.max$}", j.user, max = max_user_len),
+                    style(Style::default().fg(Color::Green)),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("{:>max$}", gpus(j), max = max_gpus_len),
+                    style(Style::default().fg(Color::Magenta)),
+                ),
+                Span::raw(if max_gpus_len > 0 { " " } else { "" }),
+                Span::styled(
+                    format!("{:>max$}", j.time.to_string(), max = max_time_len),
+                    style(Style::default().fg(Color::Red)),
+                ),
+                Span::raw(if max_remaining_len > 0 { " " } else { "" }),
+                Span::styled(
+                    format!("{:>max$}", remaining(j), max = max_remaining_len),
+                    style(Style::default().fg(Color::Cyan)),
+                ),
+                Span::raw(" "),
+                Span::styled(&j.name, style(Style::default())),
+            ]))
+        };
+        let jobs: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| match row {
+                Row::Job(i) => job_item(*i),
+                Row::Array {
+                    tasks, expanded, ..
+                } => {
+                    let first = &self.jobs[tasks[0]];
+                    let all_marked = tasks
+                        .iter()
+                        .all(|i| self.marked.contains(&self.jobs[*i].id()));
+                    let mark = match (self.marked.is_empty(), all_marked) {
+                        (true, _) => Span::raw(""),
+                        (false, true) => Span::styled("▌", Style::default().fg(Color::Magenta)),
+                        (false, false) => Span::raw(" "),
+                    };
+                    let mut spans = vec![
+                        mark,
+                        Span::raw(if *expanded { "▾ " } else { "▸ " }),
+                        Span::raw(" ".repeat(max_state_compact_len + 1)),
+                        Span::styled(
+                            format!("{:<max$.max$}", row.id(&self.jobs), max = max_id_len),
+                            Style::default().fg(Color::Yellow),
+                        ),
+                        Span::raw(" "),
+                        Span::styled(
+                            format!("{:<max$.max$}", first.partition, max = max_partition_len),
+                            Style::default().fg(Color::Blue),
+                        ),
+                        Span::raw(" "),
+                        Span::styled(
+                            format!("{:<max$.max$}", first.user, max = max_user_len),
+                            Style::default().fg(Color::Green),
+                        ),
+                        Span::raw(" "),
+                    ];
+                    // the counts come first, they matter more than the name
+                    spans.extend(self.state_count_spans(&self.task_states(tasks)));
+                    spans.push(Span::raw(format!(
+                        " {} ({} tasks)",
+                        first.name,
+                        tasks.len()
+                    )));
+                    ListItem::new(Line::from(spans))
+                }
+            })
+            .collect();
+        // a legend of the states in the list
+        let mut legend = vec![Span::raw("─")];
+        let mut seen = HashSet::new();
+        for (state, _) in &states {
+            if seen.insert(state) {
+                legend.extend([
+                    Span::styled(state.compact(), self.theme.state(state)),
+                    Span::styled(
+                        format!(" {} ", state.as_str().to_lowercase()),
+                        Style::default().add_modifier(Modifier::DIM),
+                    ),
+                ]);
+            }
+        }
+        let job_list = List::new(jobs)
+            .block(
+                Block::default()
+                    .title_bottom(Line::from(legend))
+                    .title(if self.marked.is_empty() {
+                        format!("─Jobs ({})", self.jobs.len())
+                    } else {
+                        let marked: Tres = self
+                            .jobs
+                            .iter()
+                            .filter(|j| self.marked.contains(&j.id()))
+                            .map(|j| &j.tres)
+                            .sum();
+                        format!(
+                            "─Jobs ({}, {} marked: {})",
+                            self.jobs.len(),
+                            self.marked.len(),
+                            marked.summary()
+                        )
+                    })
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(self.border_style(Focus::Jobs)),
+            )
+            .highlight_style(Style::default().bg(Color::Green).fg(Color::Black));
+        if self.matrix.is_some() {
+            self.matrix_columns = self.render_matrix(f, master_detail[0]);
+        } else {
+            f.render_stateful_widget(job_list, master_detail[0], &mut self.job_list_state);
+        }
+        self.job_list_height = master_detail[0].height.saturating_sub(2); // account for borders
 
-            Text::from(vec![state, command, nodes, tres, stdout])
-        });
-        let job_detail = Paragraph::new(job_detail.unwrap_or_default()).block(
+        let job_detail = job_detail.unwrap_or_default();
+        let offset = self.job_details_scroll.update(
+            job_detail.height(),
+            job_detail_log[0].height.saturating_sub(2),
+        );
+        let job_detail = Paragraph::new(job_detail).scroll((offset, 0)).block(
             Block::default()
                 .title("─Details")
                 .borders(Borders::ALL)
-                .border_type(BorderType::Rounded),
+                .border_type(BorderType::Rounded)
+                .border_style(self.border_style(Focus::Details)),
         );
         f.render_widget(job_detail, job_detail_log[0]);
 
@@ -527,8 +2607,11 @@ impl App {
                 Style::default().add_modifier(Modifier::DIM),
             ),
         ]);
-        let log_block = Block::default().title(log_title).borders(Borders::ALL);
-        let log_block = log_block.border_type(BorderType::Rounded);
+        let log_block = Block::default()
+            .title(log_title)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.border_style(Focus::Log));
 
         // let job_log = self.job_stdout.as_deref().map(|s| {
         //     string_for_paragraph(
@@ -558,48 +2641,397 @@ impl App {
         .block(log_block);
 
         f.render_widget(log, log_area);
+    }
 
-        if let Some(dialog) = &self.dialog {
-            fn centered_lines(percent_x: u16, lines: u16, r: Rect) -> Rect {
-                let dy = r.height.saturating_sub(lines) / 2;
-                let r = Rect::new(r.x, r.y + dy, r.width, min(lines, r.height - dy));
-
-                Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints(
-                        [
-                            Constraint::Percentage((100 - percent_x) / 2),
-                            Constraint::Percentage(percent_x),
-                            Constraint::Percentage((100 - percent_x) / 2),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(r)[1]
-            }
+    /// One cell per task, scrolled so that the cursor is visible.
+    /// Returns the number of cells per row.
+    fn render_matrix(&self, f: &mut Frame, area: Rect) -> u16 {
+        let Some(matrix) = &self.matrix else {
+            return 1;
+        };
+        let cells = self.matrix_cells(matrix);
+        let columns = (area.width.saturating_sub(2) / 2).max(1);
+        let height = area.height.saturating_sub(2) as usize;
+        let cursor = cells.iter().position(|(index, ..)| *index == matrix.cursor);
+        let first_row =
+            (cursor.unwrap_or(0) / columns as usize).saturating_sub(height.saturating_sub(1));
+        let lines: Vec<Line> = cells
+            .chunks(columns as usize)
+            .skip(first_row)
+            .take(height)
+            .map(|row| {
+                Line::from(
+                    row.iter()
+                        .flat_map(|(index, state, _)| {
+                            let style = self.theme.state(state);
+                            let style = if *index == matrix.cursor {
+                                style.add_modifier(Modifier::REVERSED)
+                            } else {
+                                style
+                            };
+                            [Span::styled("■", style), Span::raw(" ")]
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
 
-            match dialog {
-                Dialog::ConfirmCancelJob(id) => {
-                    let dialog = Paragraph::new(Line::from(vec![
-                        Span::raw("Cancel job "),
-                        Span::styled(id, Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw("?"),
-                    ]))
-                    .style(Style::default().fg(Color::White))
-                    .wrap(Wrap { trim: true })
-                    .block(
-                        Block::default()
-                            .title("─Confirm")
-                            .borders(Borders::ALL)
-                            .border_type(BorderType::Rounded)
-                            .style(Style::default().fg(Color::Green)),
-                    );
+        let mut title = vec![Span::raw(format!("─Array {} ", matrix.array_id))];
+        let states: Vec<JobState> = cells.iter().map(|(_, state, _)| state.clone()).collect();
+        title.extend(self.state_count_spans(&states));
+        // the task under the cursor, or why finished tasks are missing
+        let bottom = match (&matrix.accounted, cursor.map(|p| &cells[p])) {
+            (Some(Err(e)), _) => Line::styled(format!("─{}", e), Style::default().fg(Color::Red)),
+            (_, Some((index, state, _))) => Line::from(vec![
+                Span::raw(format!("─{}_{} ", matrix.array_id, index)),
+                Span::styled(state.as_str().to_owned(), self.theme.state(state)),
+            ]),
+            (_, None) => Line::raw(""),
+        };
+        let grid = Paragraph::new(lines).block(
+            Block::default()
+                .title(Line::from(title))
+                .title_bottom(bottom)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Green)),
+        );
+        f.render_widget(grid, area);
+        columns
+    }
+}
 
-                    let area = centered_lines(75, 3, f.area());
-                    f.render_widget(Clear, area);
-                    f.render_widget(dialog, area);
-                }
+fn centered_lines(percent_x: u16, lines: u16, r: Rect) -> Rect {
+    let dy = r.height.saturating_sub(lines) / 2;
+    let r = Rect::new(r.x, r.y + dy, r.width, min(lines, r.height - dy));
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r)[1]
+}
+
+/// Runs an interactive command on the terminal.
+/// Failures are shown until enter is pressed, the TUI would hide them otherwise.
+fn run_foreground(foreground: &mut Foreground) {
+    let error = match foreground.command.status() {
+        Ok(status) if status.success() => return,
+        Ok(status) => format!("{} exited with {}", foreground.description, status),
+        Err(e) => format!("failed to execute {}: {}", foreground.description, e),
+    };
+    print!("turm: {}, press enter to return", error);
+    let _ = io::stdout().flush();
+    let _ = io::stdin().read_line(&mut String::new());
+}
+
+/// A batch script with line numbers and highlighting.
+fn script_lines(script: &str) -> Vec<Line<'static>> {
+    let width = script.lines().count().to_string().len();
+    script
+        .lines()
+        .enumerate()
+        .map(|(i, l)| {
+            let mut line = highlight::shell_line(l);
+            line.spans.insert(
+                0,
+                Span::styled(
+                    format!("{:>width$} ", i + 1),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            );
+            line
+        })
+        .collect()
+}
+
+/// Renders a dialog with a form, which is `None` while loading.
+fn render_form_dialog(
+    f: &mut Frame,
+    title: String,
+    form: Option<Result<&Form, &String>>,
+    hint: &str,
+) {
+    let (lines, height) = match form {
+        None => (
+            vec![Line::styled(
+                "Loading…",
+                Style::default().add_modifier(Modifier::DIM),
+            )],
+            1,
+        ),
+        Some(Err(e)) => (
+            vec![Line::styled(e.as_str(), Style::default().fg(Color::Red))],
+            1,
+        ),
+        Some(Ok(form)) => {
+            let mut lines = form.lines();
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                hint,
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+            (lines, form.height() + 2)
+        }
+    };
+    let dialog = Paragraph::new(lines)
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(Color::Green)),
+        );
+
+    let area = centered_lines(75, height + 2, f.area());
+    f.render_widget(Clear, area);
+    f.render_widget(dialog, area);
+}
+
+fn toast_line(entry: &ActionEntry) -> Line<'_> {
+    let description = entry.action.description();
+    match &entry.status {
+        ActionStatus::Queued | ActionStatus::Running => Line::styled(
+            format!("⋯ {}", description),
+            Style::default().fg(Color::Yellow),
+        ),
+        ActionStatus::Succeeded(output) => Line::styled(
+            match output.lines().next() {
+                Some(output) => format!("✓ {}: {}", description, output),
+                None => format!("✓ {}", description),
+            },
+            Style::default().fg(Color::Green),
+        ),
+        ActionStatus::Failed(e) => Line::styled(
+            format!(
+                "✗ {}: {}",
+                description,
+                e.lines().next().unwrap_or_default()
+            ),
+            Style::default().fg(Color::Red),
+        ),
+    }
+}
+
+/// The TRES as a table of a line of names and a line of amounts.
+fn tres_lines(tres: &Tres) -> Vec<Line<'static>> {
+    let label = Span::styled("TRES   ", Style::default().fg(Color::Yellow));
+    let columns = tres.table();
+    if columns.is_empty() {
+        return vec![Line::from(vec![label])];
+    }
+    let mut names = vec![label, Span::raw(" ")];
+    let mut amounts = vec![Span::raw("        ")];
+    for (name, amount) in columns {
+        let width = name.chars().count().max(amount.chars().count()) + 2;
+        names.push(Span::styled(
+            format!("{:<width$}", name),
+            Style::default().add_modifier(Modifier::DIM),
+        ));
+        amounts.push(Span::raw(format!("{:<width$}", amount)));
+    }
+    vec![Line::from(names), Line::from(amounts)]
+}
+
+/// Jobs grouped by `key`, with the number of running and pending jobs and the TRES of the running ones.
+fn totals<'a>(
+    jobs: &[&'a Job],
+    key: impl Fn(&'a Job) -> &'a str,
+) -> Vec<(&'a str, usize, usize, Tres)> {
+    let mut groups: BTreeMap<&str, (usize, usize, Tres)> = BTreeMap::new();
+    for job in jobs {
+        let (running, pending, tres) = groups.entry(key(job)).or_default();
+        match job.state {
+            JobState::Running => {
+                *running += 1;
+                *tres += &job.tres;
             }
+            JobState::Pending => *pending += 1,
+            _ => {}
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(key, (running, pending, tres))| (key, running, pending, tres))
+        .collect()
+}
+
+/// Table columns as wide as their header and cells, but the last one fills the rest.
+pub fn column_widths<const N: usize>(header: &[&str; N], rows: &[[String; N]]) -> Vec<Constraint> {
+    let mut widths: Vec<Constraint> = header
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            let width = rows
+                .iter()
+                .map(|row| row[i].len())
+                .fold(h.len(), usize::max);
+            Constraint::Length(width as u16)
+        })
+        .collect();
+    if let Some(last) = widths.last_mut() {
+        *last = Constraint::Fill(1);
+    }
+    widths
+}
+
+/// Green for the panel with the `focus`, which is `None` while a dialog is open.
+pub fn border_style(focus: Option<Focus>, panel: Focus) -> Style {
+    if focus == Some(panel) {
+        Style::default().fg(Color::Green)
+    } else {
+        Style::default()
+    }
+}
+
+/// The [`Efficiency::summary`] and a warning per over-requested resource, `…` while loading.
+pub fn efficiency_lines(
+    report: Option<&Result<Efficiency, String>>,
+    label: &'static str,
+    warning_label: &'static str,
+) -> Vec<Line<'static>> {
+    let line = |l: &'static str, span: Span<'static>| {
+        Line::from(vec![
+            Span::styled(l, Style::default().fg(Color::Yellow)),
+            Span::raw(" "),
+            span,
+        ])
+    };
+    match report {
+        None => vec![line(
+            label,
+            Span::styled("…", Style::default().add_modifier(Modifier::DIM)),
+        )],
+        Some(Err(e)) => vec![line(
+            label,
+            Span::styled(e.clone(), Style::default().fg(Color::Red)),
+        )],
+        Some(Ok(efficiency)) => std::iter::once(line(label, Span::raw(efficiency.summary())))
+            .chain(efficiency.over_requests().into_iter().map(|w| {
+                line(
+                    warning_label,
+                    Span::styled(w, Style::default().fg(Color::Magenta)),
+                )
+            }))
+            .collect(),
+    }
+}
+
+/// The latest usage and sparklines of the recent samples.
+fn usage_lines(usage: &JobUsage) -> Vec<Line<'static>> {
+    let label = |s: &'static str| Span::styled(s, Style::default().fg(Color::Yellow));
+    let spark = |values: &[f64]| Span::styled(sparkline(values), Style::default().fg(Color::Blue));
+    let mut lines = Vec::new();
+    if let Some(e) = &usage.error {
+        lines.push(Line::from(vec![
+            label("Usage  "),
+            Span::raw(" "),
+            Span::styled(e.clone(), Style::default().fg(Color::Red)),
+        ]));
+    }
+    let samples: Vec<&Sample> = usage.samples.iter().collect();
+    let Some(last) = samples.last() else {
+        if lines.is_empty() {
+            lines.push(Line::from(vec![
+                label("Usage  "),
+                Span::raw(" "),
+                Span::styled("…", Style::default().add_modifier(Modifier::DIM)),
+            ]));
         }
+        return lines;
+    };
+    // per second, between consecutive samples
+    let rates = |value: fn(&Sample) -> f64| -> Vec<f64> {
+        samples
+            .windows(2)
+            .map(|w| {
+                let seconds = w[1].at.duration_since(w[0].at).as_secs_f64();
+                (value(w[1]) - value(w[0])).max(0.0) / seconds.max(1.0)
+            })
+            .collect()
+    };
+    let cpu = rates(|s| s.cpu_time.as_secs_f64());
+    let disk = rates(|s| (s.disk_read + s.disk_write) as f64);
+    let rss: Vec<f64> = samples.iter().map(|s| s.max_rss as f64).collect();
+    lines.push(Line::from(vec![
+        label("CPU    "),
+        Span::raw(" "),
+        Span::raw(match cpu.last() {
+            Some(cores) => format!("{:.1} cores ", cores),
+            None => "… ".to_owned(),
+        }),
+        Span::raw(format!("(AveCPU {}) ", format_elapsed(last.ave_cpu))),
+        spark(&cpu),
+    ]));
+    lines.push(Line::from(vec![
+        label("Memory "),
+        Span::raw(" "),
+        Span::raw(format!("MaxRSS {} ", slurm::format_size(last.max_rss))),
+        spark(&rss),
+    ]));
+    lines.push(Line::from(vec![
+        label("Disk   "),
+        Span::raw(" "),
+        Span::raw(format!(
+            "read {} write {} ",
+            slurm::format_size(last.disk_read),
+            slurm::format_size(last.disk_write)
+        )),
+        Span::raw(match disk.last() {
+            Some(rate) => format!("({}/s) ", slurm::format_size(*rate as u64)),
+            None => String::new(),
+        }),
+        spark(&disk),
+    ]));
+    if let Some(util) = last.gpu_util {
+        let gpu: Vec<f64> = samples.iter().filter_map(|s| s.gpu_util).collect();
+        lines.push(Line::from(vec![
+            label("GPU    "),
+            Span::raw(" "),
+            Span::raw(format!("{:.0}% ", util)),
+            spark(&gpu),
+        ]));
+    }
+    lines
+}
+
+/// A bar like `██████░░░░` filled by `fraction`.
+fn progress_bar(fraction: f64, width: usize) -> String {
+    let filled = ((fraction.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    "█".repeat(filled) + &"░".repeat(width - filled)
+}
+
+/// Bars scaled between the smallest and the largest value.
+fn sparkline(values: &[f64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if max > min {
+                BARS[((v - min) / (max - min) * 7.0).round() as usize]
+            } else {
+                BARS[0]
+            }
+        })
+        .collect()
+}
+
+fn format_elapsed(d: Duration) -> String {
+    let s = d.as_secs();
+    match s {
+        0..60 => format!("{}s", s),
+        60..3600 => format!("{}m", s / 60),
+        _ => format!("{}h", s / 3600),
     }
 }
 
@@ -624,51 +3056,6 @@ fn chunked_string(s: &str, first_chunk_size: usize, chunk_size: usize) -> Vec<&s
     iter.chain(once(&s[last_index..])).collect()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_chunked_string() {
-        // Divisible
-        let input = "abcdefghij";
-        let expected = vec!["abcd", "ef", "gh", "ij"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Not divisible
-        let input = "123456789";
-        let expected = vec!["1234", "56", "78", "9"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Smaller
-        let input = "abc";
-        let expected = vec!["abc"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Smaller
-        let input = "abcde";
-        let expected = vec!["abcd", "e"];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        // Empty
-        let input = "";
-        let expected: Vec<&str> = vec![""];
-        assert_eq!(chunked_string(input, 4, 2), expected);
-
-        let input = "123456789";
-        let expected = vec!["1234", "56789"];
-        assert_eq!(chunked_string(input, 4, 0), expected);
-
-        let input = "123456789";
-        let expected = vec!["12", "34", "56", "78", "9"];
-        assert_eq!(chunked_string(input, 0, 2), expected);
-
-        let input = "123456789";
-        let expected = vec!["123456789"];
-        assert_eq!(chunked_string(input, 0, 0), expected);
-    }
-}
-
 fn fit_text(
     s: &'_ str,
     lines: usize,
@@ -740,16 +3127,170 @@ fn fit_text(
 }
 
 impl App {
-    fn focus_next_panel(&mut self) {
-        match self.focus {
-            Focus::Jobs => self.focus = Focus::Jobs,
+    fn border_style(&self, panel: Focus) -> Style {
+        border_style(self.dialog.is_none().then_some(self.focus), panel)
+    }
+
+    /// Moves the focus to the previous or next panel of the tab.
+    fn move_focus(&mut self, next: bool) {
+        let panels = Focus::panels(self.tab);
+        let i = panels.iter().position(|p| *p == self.focus).unwrap_or(0);
+        let i = if next {
+            (i + 1).min(panels.len() - 1)
+        } else {
+            i.saturating_sub(1)
+        };
+        self.focus = panels[i];
+    }
+
+    /// Returns whether the key was consumed.
+    fn handle_job_list_key(&mut self, key: KeyEvent) -> bool {
+        let control = key
+            .modifiers
+            .contains(crossterm::event::KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('k') | KeyCode::Up => self.select_previous_job(),
+            KeyCode::Char('j') | KeyCode::Down => self.select_next_job(),
+            KeyCode::Char('g') => self.select_first_job(),
+            KeyCode::Char('G') => self.select_last_job(),
+            KeyCode::Char('u') if control => self.scroll_jobs_half_page_up(),
+            KeyCode::Char('d') if control => self.scroll_jobs_half_page_down(),
+            KeyCode::PageUp => self.scroll_jobs_half_page_up(),
+            KeyCode::PageDown => self.scroll_jobs_half_page_down(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Returns whether the key was consumed.
+    fn handle_log_key(&mut self, key: KeyEvent) -> bool {
+        let page = if key.modifiers.intersects(
+            crossterm::event::KeyModifiers::SHIFT
+                | crossterm::event::KeyModifiers::CONTROL
+                | crossterm::event::KeyModifiers::ALT,
+        ) {
+            50
+        } else {
+            1
+        };
+        // towards the end of the log or its start
+        let (down, delta) = match key.code {
+            KeyCode::Char('j') | KeyCode::Down => (true, 1),
+            KeyCode::Char('k') | KeyCode::Up => (false, 1),
+            KeyCode::PageDown => (true, page),
+            KeyCode::PageUp => (false, page),
+            KeyCode::Char('g') => {
+                self.job_output_offset = 0;
+                self.job_output_anchor = ScrollAnchor::Top;
+                return true;
+            }
+            KeyCode::Char('G') => {
+                self.job_output_offset = 0;
+                self.job_output_anchor = ScrollAnchor::Bottom;
+                return true;
+            }
+            _ => return false,
+        };
+        self.job_output_offset = match (self.job_output_anchor, down) {
+            (ScrollAnchor::Top, true) | (ScrollAnchor::Bottom, false) => {
+                self.job_output_offset.saturating_add(delta)
+            }
+            _ => self.job_output_offset.saturating_sub(delta),
+        };
+        true
+    }
+
+    /// Runs `query` on a new thread and sends its result to the app.
+    fn spawn_query(&self, query: impl FnOnce() -> AppMessage + Send + 'static) {
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let _ = sender.send(query());
+        });
+    }
+
+    fn run_action(&mut self, action: Action) {
+        let id = self.action_runner.run(action.clone());
+        self.actions.push(ActionEntry {
+            id,
+            action,
+            status: ActionStatus::Queued,
+            queued_at: Instant::now(),
+        });
+        self.toasts.push(Toast {
+            action_id: id,
+            expires: None,
+        });
+    }
+
+    fn selected_row(&self) -> Option<&Row> {
+        self.job_list_state
+            .selected()
+            .and_then(|i| self.rows.get(i))
+    }
+
+    /// The task under the cursor of the matrix while it is open, `None` if an array is selected.
+    fn selected_job_index(&self) -> Option<usize> {
+        if let Some(matrix) = &self.matrix {
+            return self
+                .matrix_cells(matrix)
+                .into_iter()
+                .find(|(index, ..)| *index == matrix.cursor)?
+                .2;
         }
+        match self.selected_row()? {
+            Row::Job(i) => Some(*i),
+            Row::Array { .. } => None,
+        }
+    }
+
+    fn selected_job(&self) -> Option<&Job> {
+        self.jobs.get(self.selected_job_index()?)
+    }
+
+    fn selected_array(&self) -> Option<&str> {
+        if self.matrix.is_some() {
+            return None;
+        }
+        match self.selected_row()? {
+            Row::Array { array_id, .. } => Some(array_id),
+            Row::Job(_) => None,
+        }
+    }
+
+    /// The index and ID of the selected row, and the array of its job.
+    fn selection(&self) -> Option<(usize, String, String)> {
+        let index = self.job_list_state.selected()?;
+        let row = self.rows.get(index)?;
+        let array_id = match row {
+            Row::Job(i) => self.jobs[*i].array_id.clone(),
+            Row::Array { array_id, .. } => array_id.clone(),
+        };
+        Some((index, row.id(&self.jobs), array_id))
     }
 
-    fn focus_previous_panel(&mut self) {
-        match self.focus {
-            Focus::Jobs => self.focus = Focus::Jobs,
+    /// Rebuilds the rows from the jobs and selects the previous [`App::selection`] again,
+    /// or its array if the task is no longer listed.
+    fn update_rows(&mut self, selection: Option<(usize, String, String)>) {
+        self.rows = job_rows::rows(&self.jobs, self.tree, &self.expanded);
+        if self.rows.is_empty() {
+            self.job_list_state.select(None);
+            return;
         }
+        let Some((index, id, array_id)) = selection else {
+            self.job_list_state.select_first();
+            return;
+        };
+        let new_index = self
+            .rows
+            .iter()
+            .position(|r| r.id(&self.jobs) == id)
+            .or_else(|| {
+                self.rows
+                    .iter()
+                    .position(|r| matches!(r, Row::Array { array_id: a, .. } if *a == array_id))
+            })
+            .unwrap_or(index.min(self.rows.len() - 1));
+        self.job_list_state.select(Some(new_index));
     }
 
     fn select_next_job(&mut self) {
@@ -776,3 +3317,48 @@ impl App {
         self.job_list_state.scroll_up_by(self.job_list_height / 2);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunked_string() {
+        // Divisible
+        let input = "abcdefghij";
+        let expected = vec!["abcd", "ef", "gh", "ij"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Not divisible
+        let input = "123456789";
+        let expected = vec!["1234", "56", "78", "9"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Smaller
+        let input = "abc";
+        let expected = vec!["abc"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Smaller
+        let input = "abcde";
+        let expected = vec!["abcd", "e"];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        // Empty
+        let input = "";
+        let expected: Vec<&str> = vec![""];
+        assert_eq!(chunked_string(input, 4, 2), expected);
+
+        let input = "123456789";
+        let expected = vec!["1234", "56789"];
+        assert_eq!(chunked_string(input, 4, 0), expected);
+
+        let input = "123456789";
+        let expected = vec!["12", "34", "56", "78", "9"];
+        assert_eq!(chunked_string(input, 0, 2), expected);
+
+        let input = "123456789";
+        let expected = vec!["123456789"];
+        assert_eq!(chunked_string(input, 0, 0), expected);
+    }
+}
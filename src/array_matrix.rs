@@ -0,0 +1,128 @@
+use std::{path::PathBuf, process::Command};
+
+use crate::job_state::JobState;
+use crate::job_watcher;
+use crate::slurm;
+
+/// A task of an array as recorded in accounting, where it stays once it left the queue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountedTask {
+    pub index: u32,
+    pub state: JobState,
+    pub exit_code: String,
+    pub stdout: Option<PathBuf>,
+    pub stderr: Option<PathBuf>,
+}
+
+const FIELDS: &str = "JobID,State,ExitCode,JobIDRaw,NodeList,User,JobName,WorkDir,StdOut,StdErr";
+/// Without the output files, which older Slurm versions don't account.
+const BASIC_FIELDS: &str = "JobID,State,ExitCode";
+
+/// All tasks of the array, including the finished ones.
+pub fn accounted_tasks(array_id: &str) -> Result<Vec<AccountedTask>, String> {
+    let sacct = |fields| {
+        slurm::output(Command::new("sacct").args([
+            "--jobs",
+            array_id,
+            "--allocations",
+            "--noheader",
+            "--parsable2",
+            "--format",
+            fields,
+        ]))
+    };
+    let output = sacct(FIELDS).or_else(|_| sacct(BASIC_FIELDS))?;
+    Ok(parse_sacct(&output))
+}
+
+/// Pending tasks share a row, e.g. `123_[4-9%2]`.
+fn parse_sacct(output: &str) -> Vec<AccountedTask> {
+    let mut tasks = Vec::new();
+    for row in output.lines() {
+        let fields: Vec<&str> = row.split('|').collect();
+        let [job_id, state, exit_code, ..] = fields[..] else {
+            continue;
+        };
+        let Some((array_id, indices)) = job_id.split_once('_') else {
+            continue;
+        };
+        let indices = parse_indices(indices);
+        // only tasks that ran have their own job ID and output files
+        let path = |index: usize| match (indices.len(), &fields[3..]) {
+            (1, [id, nodelist, user, name, working_dir, stdout, stderr]) => {
+                job_watcher::resolve_path(
+                    [stdout, stderr][index],
+                    array_id,
+                    &indices[0].to_string(),
+                    id,
+                    nodelist,
+                    user,
+                    name,
+                    working_dir,
+                )
+            }
+            _ => None,
+        };
+        let (stdout, stderr) = (path(0), path(1));
+        tasks.extend(indices.iter().map(|index| AccountedTask {
+            index: *index,
+            state: JobState::parse(state),
+            exit_code: exit_code.to_owned(),
+            stdout: stdout.clone(),
+            stderr: stderr.clone(),
+        }));
+    }
+    tasks
+}
+
+/// The indices of an array step like `7` or `[1-3,5%2]`, ignoring the limit of running tasks.
+pub fn parse_indices(s: &str) -> Vec<u32> {
+    let s = s.trim_start_matches('[').trim_end_matches(']');
+    let s = s.split_once('%').map_or(s, |(ranges, _)| ranges);
+    let mut indices = Vec::new();
+    for range in s.split(',') {
+        // ranges may have a step, e.g. `0-15:4`
+        let (range, step) = range.split_once(':').unwrap_or((range, "1"));
+        let step = step.parse::<usize>().unwrap_or(1).max(1);
+        match range.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<u32>(), end.parse::<u32>()) {
+                    indices.extend((start..=end).step_by(step));
+                }
+            }
+            None => indices.extend(range.parse::<u32>().ok()),
+        }
+    }
+    indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sacct() {
+        assert_eq!(parse_indices("[1-3,7%2]"), [1, 2, 3, 7]);
+        assert_eq!(parse_indices("0-8:4"), [0, 4, 8]);
+        assert_eq!(parse_indices("5"), [5]);
+        let tasks = parse_sacct("200_0|COMPLETED|0:0\n200_1|FAILED|1:0\n200_[2-3]|PENDING|0:0\n");
+        assert_eq!(tasks.len(), 4);
+        assert_eq!(tasks[1].state, JobState::Failed);
+        assert_eq!(tasks[1].exit_code, "1:0");
+        assert_eq!(tasks[3].index, 3);
+        assert_eq!(tasks[3].state, JobState::Pending);
+        let tasks = parse_sacct(
+            "200_1|FAILED|1:0|201|n1|alice|sim|/home/alice|/home/alice/%x-%A_%a.out|/home/alice/%x-%j.err\n\
+             200_[2-3]|PENDING|0:0|200|None assigned|alice|sim|/home/alice||\n",
+        );
+        assert_eq!(
+            tasks[0].stdout,
+            Some(PathBuf::from("/home/alice/sim-200_1.out"))
+        );
+        assert_eq!(
+            tasks[0].stderr,
+            Some(PathBuf::from("/home/alice/sim-201.err"))
+        );
+        assert_eq!(tasks[1].stdout, None);
+    }
+}
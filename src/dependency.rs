@@ -0,0 +1,260 @@
+use std::collections::HashSet;
+
+use crate::app::Job;
+
+/// A dependency on another job as `squeue` prints it, e.g. `afterok:123_*(unfulfilled)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dependency {
+    /// E.g. `afterok` or `afterany`.
+    pub kind: String,
+    /// A job, an array task like `123_4`, or a whole array like `123_*`.
+    pub job_id: String,
+    /// E.g. `unfulfilled`, or `failed` once it can never be satisfied.
+    pub status: String,
+}
+
+impl Dependency {
+    pub fn never_satisfied(&self) -> bool {
+        self.status == "failed"
+    }
+}
+
+/// Dependencies joined by `,` (all of) or `?` (any of). `singleton` does not name a job
+/// and is left out.
+pub fn parse(s: &str) -> Vec<Dependency> {
+    let mut dependencies = Vec::new();
+    for item in s.split([',', '?']) {
+        let (item, status) = match item.split_once('(') {
+            Some((item, status)) => (item, status.trim_end_matches(')')),
+            None => (item, ""),
+        };
+        let Some((kind, job_ids)) = item.split_once(':') else {
+            continue;
+        };
+        // `after` may give a delay in minutes, e.g. `after:123+10`
+        for job_id in job_ids.split(':') {
+            let job_id = job_id.split_once('+').map_or(job_id, |(id, _)| id);
+            dependencies.push(Dependency {
+                kind: kind.to_owned(),
+                job_id: job_id.to_owned(),
+                status: status.to_owned(),
+            });
+        }
+    }
+    dependencies
+}
+
+/// Array tasks share their dependencies, so an array is a single node, e.g. `123_*`.
+pub fn node_id(job: &Job) -> String {
+    match job.array_step {
+        Some(_) => format!("{}_*", job.array_id),
+        None => job.job_id.clone(),
+    }
+}
+
+/// Whether a node ID refers to the same job or task as another, e.g. `123_*` and `123_4`.
+pub fn overlaps(a: &str, b: &str) -> bool {
+    let covers = |array: &str, id: &str| {
+        array
+            .strip_suffix("_*")
+            .is_some_and(|array| id.strip_prefix(array).is_some_and(|t| t.starts_with('_')))
+    };
+    a == b || covers(a, b) || covers(b, a)
+}
+
+/// The edge from the job a child depends on to the child.
+#[derive(Debug, PartialEq)]
+pub struct Edge {
+    pub parent: String,
+    pub child: String,
+    pub dependency: Dependency,
+}
+
+/// The edges between the jobs, without duplicates of array tasks.
+pub fn edges(jobs: &[Job]) -> Vec<Edge> {
+    let mut edges: Vec<Edge> = Vec::new();
+    for job in jobs {
+        let child = node_id(job);
+        for dependency in &job.dependencies {
+            let edge = Edge {
+                parent: dependency.job_id.clone(),
+                child: child.clone(),
+                dependency: dependency.clone(),
+            };
+            if !edges.contains(&edge) {
+                edges.push(edge);
+            }
+        }
+    }
+    edges
+}
+
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Ancestors,
+    Descendants,
+}
+
+/// A node of the tree of ancestors or descendants of a job.
+#[derive(Debug, PartialEq)]
+pub struct TreeLine<'a> {
+    /// Box drawing of the branches, e.g. `│  └─`.
+    pub prefix: String,
+    pub id: &'a str,
+    pub dependency: &'a Dependency,
+    /// Listed before, its branches are not repeated.
+    pub repeated: bool,
+    /// Whether the dependency can never be satisfied, or for descendants one on the way from the root.
+    pub never_satisfied: bool,
+}
+
+/// The DAG as a tree, where nodes reached twice are repeated without their branches.
+/// `blocked` are the nodes Slurm found to wait for a dependency that is never satisfied.
+pub fn tree<'a>(
+    edges: &'a [Edge],
+    root: &str,
+    direction: Direction,
+    blocked: &HashSet<String>,
+) -> Vec<TreeLine<'a>> {
+    let mut lines = Vec::new();
+    let mut seen = HashSet::from([root.to_owned()]);
+    let walk = Walk {
+        edges,
+        direction,
+        blocked,
+    };
+    // the descendants of a blocked job are blocked as well
+    walk.walk(root, "", blocked.contains(root), &mut seen, &mut lines);
+    lines
+}
+
+struct Walk<'a, 'b> {
+    edges: &'a [Edge],
+    direction: Direction,
+    blocked: &'b HashSet<String>,
+}
+
+impl<'a> Walk<'a, '_> {
+    fn walk(
+        &self,
+        id: &str,
+        indent: &str,
+        never_satisfied: bool,
+        seen: &mut HashSet<String>,
+        lines: &mut Vec<TreeLine<'a>>,
+    ) {
+        let next: Vec<(&str, &Dependency)> = self
+            .edges
+            .iter()
+            .filter_map(|e| match self.direction {
+                Direction::Ancestors if overlaps(&e.child, id) => {
+                    Some((e.parent.as_str(), &e.dependency))
+                }
+                Direction::Descendants if overlaps(&e.parent, id) => {
+                    Some((e.child.as_str(), &e.dependency))
+                }
+                _ => None,
+            })
+            .collect();
+        for (i, (next_id, dependency)) in next.iter().enumerate() {
+            let last = i + 1 == next.len();
+            let repeated = !seen.insert(next_id.to_string());
+            // the job that waits for the dependency
+            let child = match self.direction {
+                Direction::Ancestors => id,
+                Direction::Descendants => next_id,
+            };
+            let never_satisfied = dependency.never_satisfied()
+                || self.blocked.contains(child)
+                || matches!(self.direction, Direction::Descendants) && never_satisfied;
+            lines.push(TreeLine {
+                prefix: format!("{}{}", indent, if last { "└─" } else { "├─" }),
+                id: next_id,
+                dependency,
+                repeated,
+                never_satisfied,
+            });
+            if !repeated {
+                let indent = format!("{}{}", indent, if last { "  " } else { "│ " });
+                self.walk(next_id, &indent, never_satisfied, seen, lines);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependencies() {
+        assert_eq!(
+            parse("afterok:10_*(unfulfilled),afterany:11:12+5(failed)"),
+            [
+                Dependency {
+                    kind: "afterok".to_owned(),
+                    job_id: "10_*".to_owned(),
+                    status: "unfulfilled".to_owned(),
+                },
+                Dependency {
+                    kind: "afterany".to_owned(),
+                    job_id: "11".to_owned(),
+                    status: "failed".to_owned(),
+                },
+                Dependency {
+                    kind: "afterany".to_owned(),
+                    job_id: "12".to_owned(),
+                    status: "failed".to_owned(),
+                },
+            ]
+        );
+        assert!(parse("(null)").is_empty());
+        assert!(parse("singleton(unfulfilled)").is_empty());
+        assert!(overlaps("10_*", "10_3"));
+        assert!(!overlaps("10_*", "100_3"));
+
+        // 1 -> 2 -> 4, 1 -> 3 -> 4, where 2 failed
+        let edge = |parent: &str, child: &str, status: &str| Edge {
+            parent: parent.to_owned(),
+            child: child.to_owned(),
+            dependency: Dependency {
+                kind: "afterok".to_owned(),
+                job_id: parent.to_owned(),
+                status: status.to_owned(),
+            },
+        };
+        let edges = [
+            edge("1", "2", "failed"),
+            edge("1", "3", ""),
+            edge("2", "4", ""),
+            edge("3", "4", ""),
+        ];
+        let lines = tree(&edges, "1", Direction::Descendants, &HashSet::new());
+        let lines: Vec<_> = lines
+            .iter()
+            .map(|l| (l.prefix.as_str(), l.id, l.repeated, l.never_satisfied))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                ("├─", "2", false, true),
+                ("│ └─", "4", false, true),
+                ("└─", "3", false, false),
+                ("  └─", "4", true, false),
+            ]
+        );
+        let ancestors = tree(
+            &edges,
+            "4",
+            Direction::Ancestors,
+            &HashSet::from(["3".to_owned()]),
+        );
+        assert_eq!(ancestors.len(), 4);
+        assert_eq!(ancestors[3].id, "1");
+        assert!(ancestors[3].repeated);
+        // 2 failed on 1, and 3 is blocked, but 4 only on the way through them
+        assert!(!ancestors[0].never_satisfied);
+        assert!(ancestors[1].never_satisfied);
+        assert!(ancestors[3].never_satisfied);
+    }
+}
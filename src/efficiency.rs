@@ -0,0 +1,191 @@
+use std::{process::Command, time::Duration};
+
+use crate::slurm;
+use crate::tres::{Tres, parse_memory};
+
+/// Jobs that used less than a quarter of what they requested are flagged.
+const OVER_REQUEST_FACTOR: f64 = 4.0;
+
+/// What a finished job used of its allocation, like `seff` reports it.
+#[derive(Clone)]
+pub struct Efficiency {
+    pub elapsed: Duration,
+    pub cpus: u64,
+    /// User and system time of all steps.
+    pub total_cpu: Duration,
+    /// Largest resident set size of any step, in bytes.
+    pub max_rss: Option<u64>,
+    /// Requested memory per node, in bytes, as `MaxRSS` is the peak of a single node.
+    pub memory: Option<u64>,
+    pub nodes: u64,
+    pub gpus: u64,
+}
+
+const FIELDS: [&str; 7] = [
+    "JobID",
+    "Elapsed",
+    "TotalCPU",
+    "MaxRSS",
+    "ReqMem",
+    "AllocTRES",
+    "NNodes",
+];
+
+/// Looks up the job and its steps in accounting.
+pub fn efficiency(job_id: &str) -> Result<Efficiency, String> {
+    let output = slurm::output(Command::new("sacct").args([
+        "--jobs",
+        job_id,
+        "--noheader",
+        "--parsable2",
+        "--format",
+        &FIELDS.join(","),
+    ]))?;
+    parse_sacct(&output).ok_or_else(|| format!("Job {} not found in accounting", job_id))
+}
+
+/// The first line is the allocation, the others are its steps.
+fn parse_sacct(output: &str) -> Option<Efficiency> {
+    let rows: Vec<Vec<&str>> = output
+        .lines()
+        .map(|l| l.split('|').collect::<Vec<_>>())
+        .filter(|r| r.len() == FIELDS.len())
+        .collect();
+    let job = rows.first()?;
+    let tres = Tres::parse(job[5]);
+    let cpus = tres.cpu;
+    let nodes = job[6].parse::<u64>().unwrap_or(1).max(1);
+    // `ReqMem` ends with `n` per node or `c` per CPU in older Slurm versions, otherwise it
+    // is the total like the allocated TRES
+    let memory = match job[4].strip_suffix('n') {
+        Some(per_node) => parse_memory(per_node),
+        None => match job[4].strip_suffix('c') {
+            Some(per_cpu) => parse_memory(per_cpu).map(|m| m * cpus.max(1) / nodes),
+            None => parse_memory(job[4]).map(|m| m / nodes),
+        },
+    }
+    .filter(|m| *m > 0)
+    .or((tres.mem > 0).then_some(tres.mem / nodes));
+    Some(Efficiency {
+        elapsed: slurm::parse_duration(job[1])?,
+        cpus,
+        total_cpu: slurm::parse_duration(job[2]).unwrap_or_default(),
+        max_rss: rows.iter().filter_map(|r| slurm::parse_size(r[3])).max(),
+        memory,
+        nodes,
+        gpus: tres.gpus(),
+    })
+}
+
+impl Efficiency {
+    /// The share of the allocated CPU time that was used.
+    pub fn cpu(&self) -> Option<f64> {
+        let allocated = self.elapsed.as_secs_f64() * self.cpus as f64;
+        (allocated > 0.0).then(|| self.total_cpu.as_secs_f64() / allocated)
+    }
+
+    /// The share of the requested memory per node that was used at most.
+    pub fn memory(&self) -> Option<f64> {
+        Some(self.max_rss? as f64 / self.memory? as f64)
+    }
+
+    pub fn gpu_hours(&self) -> f64 {
+        self.gpus as f64 * self.elapsed.as_secs_f64() / 3600.0
+    }
+
+    /// ` per node` if the job ran on several nodes.
+    fn per_node(&self) -> &'static str {
+        if self.nodes > 1 { " per node" } else { "" }
+    }
+
+    /// E.g. `CPU 23% of 8 cores, memory 12% of 64G, 3.2 GPU hours`.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(cpu) = self.cpu() {
+            parts.push(format!("CPU {:.0}% of {} cores", cpu * 100.0, self.cpus));
+        }
+        match (self.memory(), self.memory) {
+            (Some(memory), Some(requested)) => parts.push(format!(
+                "memory {:.0}% of {}{}",
+                memory * 100.0,
+                slurm::format_size(requested),
+                self.per_node()
+            )),
+            (None, Some(requested)) => parts.push(format!(
+                "memory ? of {}{}",
+                slurm::format_size(requested),
+                self.per_node()
+            )),
+            _ => {}
+        }
+        if self.gpus > 0 {
+            parts.push(format!("{:.1} GPU hours", self.gpu_hours()));
+        }
+        parts.join(", ")
+    }
+
+    /// A sentence per resource the job requested far more of than it used.
+    pub fn over_requests(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(cpu) = self
+            .cpu()
+            .filter(|c| *c * OVER_REQUEST_FACTOR < 1.0 && self.cpus > 1)
+        {
+            warnings.push(format!(
+                "Requested {} cores but kept only {:.1} busy on average.",
+                self.cpus,
+                cpu * self.cpus as f64
+            ));
+        }
+        if let (Some(memory), Some(requested), Some(max_rss)) =
+            (self.memory(), self.memory, self.max_rss)
+        {
+            if memory * OVER_REQUEST_FACTOR < 1.0 {
+                warnings.push(format!(
+                    "Requested {} memory{} but used at most {}.",
+                    slurm::format_size(requested),
+                    self.per_node(),
+                    slurm::format_size(max_rss)
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sacct() {
+        let output = "\
+123|01:00:00|04:00:00||64G|billing=8,cpu=8,gres/gpu:a100=2,mem=64G,node=1|1
+123.batch|01:00:00|04:00:00|8G||cpu=8,gres/gpu:a100=2,mem=64G,node=1|1
+123.extern|01:00:00|00:00:00|1M||billing=8,cpu=8,gres/gpu:a100=2,mem=64G,node=1|1
+";
+        let efficiency = parse_sacct(output).unwrap();
+        assert_eq!(efficiency.cpu(), Some(0.5));
+        assert_eq!(efficiency.memory(), Some(0.125));
+        assert_eq!(efficiency.gpu_hours(), 2.0);
+        assert_eq!(
+            efficiency.summary(),
+            "CPU 50% of 8 cores, memory 12% of 64G, 2.0 GPU hours"
+        );
+        assert_eq!(
+            efficiency.over_requests(),
+            ["Requested 64G memory but used at most 8.0G."]
+        );
+
+        let per_cpu = parse_sacct("7|00:10:00|00:01:00||2000Mc|cpu=4,mem=8000M,node=1|1").unwrap();
+        assert_eq!(per_cpu.memory, Some(8000 * 1024 * 1024));
+        assert_eq!(per_cpu.max_rss, None);
+        let nodes = parse_sacct("8|01:00:00|04:00:00|16G|128G|cpu=8,mem=128G,node=2|2").unwrap();
+        assert_eq!(nodes.memory(), Some(0.25));
+        assert_eq!(
+            nodes.summary(),
+            "CPU 50% of 8 cores, memory 25% of 64G per node"
+        );
+        assert!(parse_sacct("").is_none());
+    }
+}
@@ -0,0 +1,253 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Single line text input with a cursor.
+pub struct TextInput {
+    value: String,
+    cursor: usize, // in chars
+}
+
+impl TextInput {
+    pub fn new(value: &str) -> Self {
+        Self {
+            value: value.to_owned(),
+            cursor: value.chars().count(),
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    fn byte_index(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.value.len(), |(i, _)| i)
+    }
+
+    /// Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.value.clear();
+                self.cursor = 0;
+            }
+            KeyCode::Char(c) => {
+                let i = self.byte_index();
+                self.value.insert(i, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    let i = self.byte_index();
+                    self.value.remove(i);
+                }
+            }
+            KeyCode::Delete => {
+                if self.cursor < self.value.chars().count() {
+                    let i = self.byte_index();
+                    self.value.remove(i);
+                }
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.value.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn spans(&self, focused: bool) -> Vec<Span<'_>> {
+        if !focused {
+            return vec![Span::raw(self.value.as_str())];
+        }
+        let i = self.byte_index();
+        let (before, rest) = self.value.split_at(i);
+        let mut rest = rest.chars();
+        let under_cursor = rest.next().map_or(" ".to_string(), |c| c.to_string());
+        vec![
+            Span::raw(before),
+            Span::styled(
+                under_cursor,
+                Style::default().add_modifier(Modifier::REVERSED),
+            ),
+            Span::raw(rest.as_str()),
+        ]
+    }
+}
+
+pub enum FieldValue {
+    Text(TextInput),
+    Choice {
+        options: Vec<String>,
+        selected: usize,
+    },
+}
+
+pub struct Field {
+    pub label: String,
+    pub value: FieldValue,
+}
+
+impl Field {
+    pub fn text(label: impl Into<String>, value: &str) -> Self {
+        Self {
+            label: label.into(),
+            value: FieldValue::Text(TextInput::new(value)),
+        }
+    }
+
+    pub fn choice(label: impl Into<String>, options: &[&str]) -> Self {
+        Self {
+            label: label.into(),
+            value: FieldValue::Choice {
+                options: options.iter().map(|s| s.to_string()).collect(),
+                selected: 0,
+            },
+        }
+    }
+}
+
+pub enum FormEvent {
+    Submit,
+    Cancel,
+    None,
+}
+
+/// A vertical list of labeled fields, navigated with up/down/tab.
+pub struct Form {
+    pub fields: Vec<Field>,
+    pub focused: usize,
+    pub error: Option<String>,
+}
+
+impl Form {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Self {
+            fields,
+            focused: 0,
+            error: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormEvent {
+        match key.code {
+            KeyCode::Enter => return FormEvent::Submit,
+            KeyCode::Esc => return FormEvent::Cancel,
+            KeyCode::Down | KeyCode::Tab => {
+                self.focused = (self.focused + 1) % self.fields.len();
+            }
+            KeyCode::Up | KeyCode::BackTab => {
+                self.focused = (self.focused + self.fields.len() - 1) % self.fields.len();
+            }
+            _ => match &mut self.fields[self.focused].value {
+                FieldValue::Text(input) => {
+                    if input.handle_key(key) {
+                        self.error = None;
+                    }
+                }
+                FieldValue::Choice { options, selected } => match key.code {
+                    KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                        *selected = (*selected + 1) % options.len();
+                        self.error = None;
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => {
+                        *selected = (*selected + options.len() - 1) % options.len();
+                        self.error = None;
+                    }
+                    _ => {}
+                },
+            },
+        }
+        FormEvent::None
+    }
+
+    fn field(&self, label: &str) -> &FieldValue {
+        &self
+            .fields
+            .iter()
+            .find(|f| f.label == label)
+            .unwrap_or_else(|| panic!("no form field {:?}", label))
+            .value
+    }
+
+    /// Trimmed value of the text field with the given label.
+    pub fn text(&self, label: &str) -> &str {
+        match self.field(label) {
+            FieldValue::Text(input) => input.value().trim(),
+            FieldValue::Choice { .. } => panic!("form field {:?} is not a text field", label),
+        }
+    }
+
+    /// Index of the selected option of the choice field with the given label.
+    pub fn choice(&self, label: &str) -> usize {
+        match self.field(label) {
+            FieldValue::Choice { selected, .. } => *selected,
+            FieldValue::Text(_) => panic!("form field {:?} is not a choice field", label),
+        }
+    }
+
+    /// Number of lines rendered by [`Form::lines`].
+    pub fn height(&self) -> u16 {
+        self.fields.len() as u16 + if self.error.is_some() { 2 } else { 0 }
+    }
+
+    pub fn lines(&self) -> Vec<Line<'_>> {
+        let label_width = self.fields.iter().map(|f| f.label.len()).max().unwrap_or(0);
+        let mut lines: Vec<Line> = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let focused = i == self.focused;
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{:<label_width$} ", field.label),
+                        if focused {
+                            Style::default().fg(Color::Green)
+                        } else {
+                            Style::default().fg(Color::Yellow)
+                        },
+                    ),
+                    Span::raw(" "),
+                ];
+                match &field.value {
+                    FieldValue::Text(input) => spans.extend(input.spans(focused)),
+                    FieldValue::Choice { options, selected } => {
+                        spans.push(Span::styled(
+                            "‹ ",
+                            Style::default().add_modifier(Modifier::DIM),
+                        ));
+                        spans.push(Span::styled(
+                            options[*selected].as_str(),
+                            if focused {
+                                Style::default().add_modifier(Modifier::REVERSED)
+                            } else {
+                                Style::default()
+                            },
+                        ));
+                        spans.push(Span::styled(
+                            " ›",
+                            Style::default().add_modifier(Modifier::DIM),
+                        ));
+                    }
+                }
+                Line::from(spans)
+            })
+            .collect();
+        if let Some(error) = &self.error {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                error.as_str(),
+                Style::default().fg(Color::Red),
+            ));
+        }
+        lines
+    }
+}
@@ -0,0 +1,156 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+const KEYWORDS: [&str; 22] = [
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+    "in", "function", "return", "exit", "export", "local", "set", "source", "srun", "module",
+];
+
+/// Basic shell syntax highlighting for one line of a batch script.
+/// `#SBATCH` directives are emphasized, since they define the job.
+pub fn shell_line(line: &str) -> Line<'static> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("#SBATCH") {
+        let indent = &line[..line.len() - trimmed.len()];
+        let (directive, options) = trimmed.split_at("#SBATCH".len());
+        return Line::from(vec![
+            Span::raw(indent.to_owned()),
+            Span::styled(
+                directive.to_owned(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                options.to_owned(),
+                Style::default()
+                    .fg(Color::LightYellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+    }
+
+    let comment = Style::default().fg(Color::DarkGray);
+    let string = Style::default().fg(Color::Green);
+    let variable = Style::default().fg(Color::Cyan);
+    let keyword = Style::default().fg(Color::Blue);
+
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut chars = line.char_indices().peekable();
+    fn flush(plain: &mut String, spans: &mut Vec<Span<'static>>) {
+        if !plain.is_empty() {
+            spans.push(Span::raw(std::mem::take(plain)));
+        }
+    }
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '#' if plain.is_empty() || plain.ends_with(char::is_whitespace) => {
+                flush(&mut plain, &mut spans);
+                spans.push(Span::styled(line[i..].to_owned(), comment));
+                return Line::from(spans);
+            }
+            '\'' | '"' => {
+                flush(&mut plain, &mut spans);
+                let mut end = line.len();
+                let mut escaped = false;
+                for (j, d) in chars.by_ref() {
+                    if d == c && !escaped {
+                        end = j + 1;
+                        break;
+                    }
+                    escaped = c == '"' && d == '\\' && !escaped;
+                }
+                spans.push(Span::styled(line[i..end].to_owned(), string));
+            }
+            '$' => {
+                flush(&mut plain, &mut spans);
+                let mut end = i + 1;
+                match chars.peek() {
+                    Some((_, '{')) => {
+                        end = line[i..].find('}').map_or(line.len(), |j| i + j + 1);
+                    }
+                    Some((_, '(')) => end = i + 2,
+                    _ => {
+                        while let Some((j, d)) = chars.peek() {
+                            if d.is_ascii_alphanumeric() || *d == '_' {
+                                end = j + 1;
+                                chars.next();
+                            } else {
+                                if end == i + 1 && "?#@*!$0123456789".contains(*d) {
+                                    end = j + 1;
+                                    chars.next();
+                                }
+                                break;
+                            }
+                        }
+                    }
+                }
+                while chars.peek().is_some_and(|(j, _)| *j < end) {
+                    chars.next();
+                }
+                spans.push(Span::styled(line[i..end].to_owned(), variable));
+            }
+            c if c.is_ascii_alphabetic()
+                && !plain.ends_with(|p: char| p.is_ascii_alphanumeric() || "_-./".contains(p)) =>
+            {
+                let mut end = i + 1;
+                while let Some((j, d)) = chars.peek() {
+                    if d.is_ascii_alphanumeric() || *d == '_' {
+                        end = j + 1;
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let word = &line[i..end];
+                let is_keyword = KEYWORDS.contains(&word)
+                    && chars
+                        .peek()
+                        .is_none_or(|(_, d)| d.is_whitespace() || ";&|)".contains(*d));
+                if is_keyword {
+                    flush(&mut plain, &mut spans);
+                    spans.push(Span::styled(word.to_owned(), keyword));
+                } else {
+                    plain.push_str(word);
+                }
+            }
+            c => plain.push(c),
+        }
+    }
+    flush(&mut plain, &mut spans);
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(line: &Line) -> Vec<String> {
+        line.spans.iter().map(|s| s.content.to_string()).collect()
+    }
+
+    #[test]
+    fn test_shell_line() {
+        assert_eq!(
+            contents(&shell_line("#SBATCH --time=1:00:00")),
+            vec!["", "#SBATCH", " --time=1:00:00"]
+        );
+        assert_eq!(
+            contents(&shell_line("echo \"hi $USER\" # greet")),
+            vec!["echo ", "\"hi $USER\"", " ", "# greet"]
+        );
+        assert_eq!(
+            contents(&shell_line("if [ -n ${X} ]; then srun a.py; fi")),
+            vec![
+                "if", " [ -n ", "${X}", " ]; ", "then", " ", "srun", " a.py; ", "fi"
+            ]
+        );
+        assert_eq!(
+            contents(&shell_line("cd $HOME/run-if/x#y")),
+            vec!["cd ", "$HOME", "/run-if/x#y"]
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use crossbeam::channel::{Receiver, Sender, unbounded};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{AppMessage, Job};
+use crate::{paths, slurm};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StateChange {
+    pub state: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Everything turm saw of a job, across sessions.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// [`Job::id`], which includes the array task.
+    pub id: String,
+    pub job_id: String,
+    pub name: String,
+    pub user: String,
+    pub partition: String,
+    pub nodelist: String,
+    pub tres: String,
+    pub command: String,
+    pub stdout: Option<PathBuf>,
+    pub stderr: Option<PathBuf>,
+    pub states: Vec<StateChange>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// From sacct, once the job left the queue.
+    #[serde(default)]
+    pub final_state: Option<String>,
+}
+
+impl HistoryEntry {
+    fn new(job: &Job, now: DateTime<Utc>) -> Self {
+        Self {
+            id: job.id(),
+            job_id: job.job_id.clone(),
+            name: job.name.clone(),
+            user: job.user.clone(),
+            partition: job.partition.clone(),
+            nodelist: job.nodelist.clone(),
+            tres: job.tres.to_string(),
+            command: job.command.clone(),
+            stdout: job.stdout.clone(),
+            stderr: job.stderr.clone(),
+            states: vec![StateChange {
+                state: job.state.to_string(),
+                at: now,
+            }],
+            first_seen: now,
+            last_seen: now,
+            final_state: None,
+        }
+    }
+
+    /// Returns whether anything worth persisting changed.
+    fn update(&mut self, job: &Job, now: DateTime<Utc>) -> bool {
+        self.last_seen = now;
+        let mut changed = false;
+        let tres = job.tres.to_string();
+        for (field, value) in [
+            (&mut self.name, &job.name),
+            (&mut self.partition, &job.partition),
+            (&mut self.nodelist, &job.nodelist),
+            (&mut self.tres, &tres),
+        ] {
+            if field != value {
+                field.clone_from(value);
+                changed = true;
+            }
+        }
+        for (field, value) in [
+            (&mut self.stdout, &job.stdout),
+            (&mut self.stderr, &job.stderr),
+        ] {
+            if field != value {
+                field.clone_from(value);
+                changed = true;
+            }
+        }
+        if self.state() != job.state.as_str() {
+            self.states.push(StateChange {
+                state: job.state.to_string(),
+                at: now,
+            });
+            changed = true;
+        }
+        changed
+    }
+
+    /// The final state if known, otherwise the last observed one.
+    pub fn state(&self) -> &str {
+        self.final_state
+            .as_deref()
+            .or(self.states.last().map(|s| s.state.as_str()))
+            .unwrap_or_default()
+    }
+
+    /// Whether all words of the query occur in the entry, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = [
+            &self.id,
+            &self.name,
+            &self.user,
+            &self.partition,
+            &self.nodelist,
+            &self.command,
+            self.state(),
+        ]
+        .map(|s| s.to_lowercase())
+        .join(" ");
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+}
+
+enum HistoryMessage {
+    /// The jobs that are new or changed, and the IDs of the ones that left the queue.
+    Observe(Vec<Job>, Vec<String>),
+    FinalState(String, String),
+    Request,
+}
+
+/// Persists observed jobs as JSON lines, a line per change of an entry.
+/// The last line of a job wins when loading.
+struct HistoryStore {
+    app: Sender<AppMessage>,
+    receiver: Receiver<HistoryMessage>,
+    path: PathBuf,
+    entries: HashMap<String, HistoryEntry>,
+    /// IDs of the jobs in the queue.
+    present: HashSet<String>,
+    /// Entries last seen longer ago are dropped.
+    retention: Option<TimeDelta>,
+    error: Option<String>,
+}
+
+pub struct HistoryHandle {
+    sender: Sender<HistoryMessage>,
+    /// Only the jobs of this user are recorded, or of all users if `None`.
+    user: Option<String>,
+    /// The jobs as sent last, to send only the ones that [`HistoryEntry::update`] changes.
+    observed: HashMap<String, HistoryEntry>,
+}
+
+impl HistoryStore {
+    fn new(
+        app: Sender<AppMessage>,
+        receiver: Receiver<HistoryMessage>,
+        path: PathBuf,
+        retention: Option<TimeDelta>,
+    ) -> Self {
+        Self {
+            app,
+            receiver,
+            path,
+            entries: HashMap::new(),
+            present: HashSet::new(),
+            retention,
+            error: None,
+        }
+    }
+
+    fn run(&mut self) {
+        match load(&self.path) {
+            Ok((entries, lines)) => {
+                self.entries = entries;
+                let expired = self.expire(Utc::now());
+                // rewrite the file once most of it is superseded or expired
+                if expired || lines > 1000 && lines > 2 * self.entries.len() {
+                    if let Err(e) = self.compact() {
+                        self.error = Some(format!("Failed to compact history: {}", e));
+                    }
+                }
+            }
+            Err(e) => self.error = Some(format!("Failed to load history: {}", e)),
+        }
+
+        while let Ok(msg) = self.receiver.recv() {
+            match msg {
+                HistoryMessage::Observe(jobs, gone) => self.observe(jobs, gone),
+                HistoryMessage::FinalState(id, state) => {
+                    if let Some(entry) = self.entries.get_mut(&id) {
+                        if entry.final_state.as_ref() != Some(&state) {
+                            entry.final_state = Some(state);
+                            let entry = entry.clone();
+                            self.append(&[entry]);
+                        }
+                    }
+                }
+                HistoryMessage::Request => {
+                    let now = Utc::now();
+                    let mut entries: Vec<_> = self.entries.values().cloned().collect();
+                    // only changes are observed, jobs in the queue are seen all the time
+                    for entry in &mut entries {
+                        if self.present.contains(&entry.id) {
+                            entry.last_seen = now;
+                        }
+                    }
+                    // newest first, jobs seen at once by descending ID
+                    entries.sort_by(|a, b| {
+                        (b.first_seen, b.job_id.len(), &b.id).cmp(&(
+                            a.first_seen,
+                            a.job_id.len(),
+                            &a.id,
+                        ))
+                    });
+                    if self
+                        .app
+                        .send(AppMessage::History(entries, self.error.clone()))
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    fn observe(&mut self, jobs: Vec<Job>, gone: Vec<String>) {
+        let now = Utc::now();
+        let mut changed = Vec::new();
+        for job in &jobs {
+            let id = job.id();
+            match self.entries.get_mut(&id) {
+                Some(entry) => {
+                    if entry.update(job, now) {
+                        changed.push(entry.clone());
+                    }
+                }
+                None => {
+                    let entry = HistoryEntry::new(job, now);
+                    changed.push(entry.clone());
+                    self.entries.insert(id.clone(), entry);
+                }
+            }
+            self.present.insert(id);
+        }
+        // persist when jobs were last seen as they leave the queue
+        for id in gone {
+            self.present.remove(&id);
+            if let Some(entry) = self.entries.get_mut(&id) {
+                entry.last_seen = now;
+                changed.push(entry.clone());
+            }
+        }
+        self.append(&changed);
+    }
+
+    /// Drops the entries last seen before the retention period, returns whether there were any.
+    fn expire(&mut self, now: DateTime<Utc>) -> bool {
+        let Some(retention) = self.retention else {
+            return false;
+        };
+        let len = self.entries.len();
+        self.entries
+            .retain(|id, e| self.present.contains(id) || now - e.last_seen < retention);
+        self.entries.len() < len
+    }
+
+    fn append(&mut self, entries: &[HistoryEntry]) {
+        if entries.is_empty() {
+            return;
+        }
+        let result = (|| {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            let mut lines = Vec::new();
+            for entry in entries {
+                serde_json::to_writer(&mut lines, entry)?;
+                lines.push(b'\n');
+            }
+            // a single write, so that concurrent turm instances do not interleave lines
+            file.write_all(&lines)
+        })();
+        if let Err(e) = result {
+            self.error = Some(format!("Failed to write {}: {}", self.path.display(), e));
+        }
+    }
+
+    fn compact(&mut self) -> io::Result<()> {
+        self.expire(Utc::now());
+        let tmp = self.path.with_extension("jsonl.tmp");
+        let mut file = io::BufWriter::new(File::create(&tmp)?);
+        for entry in self.entries.values() {
+            serde_json::to_writer(&mut file, entry)?;
+            file.write_all(b"\n")?;
+        }
+        file.flush()?;
+        fs::rename(tmp, &self.path)
+    }
+}
+
+/// Returns the entries and the number of lines read. Unreadable lines are skipped.
+fn load(path: &Path) -> io::Result<(HashMap<String, HistoryEntry>, usize)> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((HashMap::new(), 0)),
+        Err(e) => return Err(e),
+    };
+    let mut entries = HashMap::new();
+    let mut lines = 0;
+    for line in BufReader::new(file).lines() {
+        lines += 1;
+        if let Ok(entry) = serde_json::from_str::<HistoryEntry>(&line?) {
+            entries.insert(entry.id.clone(), entry);
+        }
+    }
+    Ok((entries, lines))
+}
+
+impl HistoryHandle {
+    /// Keeps entries for `retention_days`, or forever if 0.
+    pub fn new(app: Sender<AppMessage>, retention_days: u64, all_users: bool) -> Self {
+        let (sender, receiver) = unbounded();
+        let retention = (retention_days > 0)
+            .then(|| TimeDelta::try_days(retention_days as i64))
+            .flatten();
+        let mut actor = HistoryStore::new(
+            app,
+            receiver,
+            paths::state_dir().join("history.jsonl"),
+            retention,
+        );
+        thread::spawn(move || actor.run());
+
+        Self {
+            sender,
+            user: (!all_users).then(slurm::current_user),
+            observed: HashMap::new(),
+        }
+    }
+
+    /// Records the jobs currently in the queue.
+    pub fn observe(&mut self, jobs: &[Job]) {
+        let jobs: Vec<&Job> = jobs
+            .iter()
+            .filter(|j| self.user.as_ref().is_none_or(|u| j.user == *u))
+            .collect();
+        let now = Utc::now();
+        let mut observed = HashMap::with_capacity(jobs.len());
+        let mut changed = Vec::new();
+        for job in jobs {
+            let id = job.id();
+            let entry = match self.observed.remove(&id) {
+                Some(mut entry) => {
+                    if entry.update(job, now) {
+                        changed.push(job.clone());
+                    }
+                    entry
+                }
+                None => {
+                    changed.push(job.clone());
+                    HistoryEntry::new(job, now)
+                }
+            };
+            observed.insert(id, entry);
+        }
+        let gone: Vec<String> = self.observed.drain().map(|(id, _)| id).collect();
+        self.observed = observed;
+        if !changed.is_empty() || !gone.is_empty() {
+            let _ = self.sender.send(HistoryMessage::Observe(changed, gone));
+        }
+    }
+
+    pub fn final_state(&self, id: String, state: String) {
+        let _ = self.sender.send(HistoryMessage::FinalState(id, state));
+    }
+
+    /// Requests all entries, which are sent as [`AppMessage::History`].
+    pub fn request(&self) {
+        let _ = self.sender.send(HistoryMessage::Request);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_state::JobState;
+
+    fn job(state: &str, nodelist: &str) -> Job {
+        Job {
+            name: "train".to_string(),
+            state: JobState::parse(state),
+            user: "alice".to_string(),
+            partition: "gpu".to_string(),
+            nodelist: nodelist.to_string(),
+            command: "/home/alice/train.sh".to_string(),
+            ..Job::test("123")
+        }
+    }
+
+    #[test]
+    fn test_history_entry() {
+        let now = Utc::now();
+        let mut entry = HistoryEntry::new(&job("PENDING", ""), now);
+        assert!(!entry.update(&job("PENDING", ""), now));
+        assert!(entry.update(&job("RUNNING", "node01"), now));
+        assert_eq!(
+            entry
+                .states
+                .iter()
+                .map(|s| s.state.as_str())
+                .collect::<Vec<_>>(),
+            vec!["PENDING", "RUNNING"]
+        );
+        assert_eq!(entry.nodelist, "node01");
+
+        entry.final_state = Some("COMPLETED".to_string());
+        assert_eq!(entry.state(), "COMPLETED");
+        assert!(entry.matches("Train completed"));
+        assert!(entry.matches(""));
+        assert!(!entry.matches("train failed"));
+    }
+
+    #[test]
+    fn test_observe() {
+        let (sender, receiver) = unbounded();
+        let mut handle = HistoryHandle {
+            sender,
+            user: None,
+            observed: HashMap::new(),
+        };
+        let sent = |handle: &mut HistoryHandle, jobs: &[Job]| {
+            handle.observe(jobs);
+            match receiver.try_recv() {
+                Ok(HistoryMessage::Observe(changed, gone)) => Some((changed.len(), gone)),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            sent(&mut handle, &[job("RUNNING", "node01")]),
+            Some((1, vec![]))
+        );
+        assert_eq!(sent(&mut handle, &[job("RUNNING", "node01")]), None);
+        let renamed = Job {
+            name: "eval".to_string(),
+            ..job("RUNNING", "node01")
+        };
+        assert_eq!(sent(&mut handle, &[renamed]), Some((1, vec![])));
+        assert_eq!(sent(&mut handle, &[]), Some((0, vec!["123".to_string()])));
+    }
+}
@@ -0,0 +1,278 @@
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap},
+};
+
+use crate::app::{Focus, border_style, efficiency_lines};
+use crate::efficiency::Efficiency;
+use crate::form::TextInput;
+use crate::history::HistoryEntry;
+use crate::scroll::Scroll;
+
+/// Past jobs from the history store, with a search.
+pub struct HistoryTab {
+    entries: Vec<HistoryEntry>,
+    error: Option<String>,
+    /// Indices of the entries that match the search.
+    matches: Vec<usize>,
+    list_state: ListState,
+    /// Rows of the list, as last rendered.
+    list_height: u16,
+    details_scroll: Scroll,
+    search: TextInput,
+    searching: bool,
+}
+
+fn local_time(t: &DateTime<Utc>, format: &str) -> String {
+    t.with_timezone(&Local).format(format).to_string()
+}
+
+impl HistoryTab {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            error: None,
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            list_height: 0,
+            details_scroll: Scroll::default(),
+            search: TextInput::new(""),
+            searching: false,
+        }
+    }
+
+    pub fn set_entries(&mut self, entries: Vec<HistoryEntry>, error: Option<String>) {
+        let selected = self.selected().map(|e| e.id.clone());
+        self.entries = entries;
+        self.error = error;
+        self.update_matches(selected);
+    }
+
+    /// Keeps the entry with the `selected` ID selected, if it still matches.
+    fn update_matches(&mut self, selected: Option<String>) {
+        let query = self.search.value();
+        self.matches = (0..self.entries.len())
+            .filter(|&i| self.entries[i].matches(query))
+            .collect();
+        let index = selected
+            .and_then(|id| self.matches.iter().position(|&i| self.entries[i].id == id))
+            .or((!self.matches.is_empty()).then_some(0));
+        self.list_state.select(index);
+    }
+
+    pub fn selected(&self) -> Option<&HistoryEntry> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.matches.get(i))
+            .map(|&i| &self.entries[i])
+    }
+
+    pub fn help() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("⏴/⏵", "panel"),
+            ("⏶/⏷", "navigate"),
+            ("/", "search"),
+            ("enter", "done"),
+            ("esc", "clear search"),
+            ("o", "toggle stdout/stderr"),
+            ("p/v", "open log in pager/editor"),
+        ]
+    }
+
+    /// Whether keys go to the search input.
+    pub fn searching(&self) -> bool {
+        self.searching
+    }
+
+    /// Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent, focus: Focus) -> bool {
+        if self.searching {
+            match key.code {
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.search = TextInput::new("");
+                    self.update_matches(self.selected().map(|e| e.id.clone()));
+                }
+                KeyCode::Up => self.list_state.select_previous(),
+                KeyCode::Down => self.list_state.select_next(),
+                _ => {
+                    let selected = self.selected().map(|e| e.id.clone());
+                    if self.search.handle_key(key) {
+                        self.update_matches(selected);
+                    }
+                }
+            }
+            return true;
+        }
+        if key.code == KeyCode::Char('/') {
+            self.searching = true;
+            return true;
+        }
+        if focus == Focus::HistoryDetails {
+            return self.details_scroll.handle_key(key);
+        }
+        let page = self.list_height.max(1);
+        match key.code {
+            KeyCode::Char('k') | KeyCode::Up => self.list_state.select_previous(),
+            KeyCode::Char('j') | KeyCode::Down => self.list_state.select_next(),
+            KeyCode::PageUp => self.list_state.scroll_up_by(page),
+            KeyCode::PageDown => self.list_state.scroll_down_by(page),
+            KeyCode::Char('g') | KeyCode::Home => self.list_state.select_first(),
+            KeyCode::Char('G') | KeyCode::End => self.list_state.select_last(),
+            _ => return false,
+        }
+        self.details_scroll.reset();
+        true
+    }
+
+    /// `efficiency` is that of the selected entry, `Some(None)` while it is looked up.
+    /// `focus` is `None` while a dialog is open.
+    pub fn render(
+        &mut self,
+        f: &mut Frame,
+        area: Rect,
+        focus: Option<Focus>,
+        efficiency: Option<Option<&Result<Efficiency, String>>>,
+    ) {
+        let master_detail = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(50), Constraint::Percentage(60)].as_ref())
+            .split(area);
+
+        let block = Block::default()
+            .title(format!(
+                "─History ({}/{})",
+                self.matches.len(),
+                self.entries.len()
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_style(focus, Focus::History));
+        let inner = block.inner(master_detail[0]);
+        f.render_widget(block, master_detail[0]);
+        let search_list = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+            .split(inner);
+
+        let dim = Style::default().add_modifier(Modifier::DIM);
+        let search = if self.searching || !self.search.value().is_empty() {
+            let mut spans = vec![Span::styled("/", Style::default().fg(Color::Blue))];
+            spans.extend(self.search.spans(self.searching));
+            Line::from(spans)
+        } else {
+            Line::styled("/ to search", dim)
+        };
+        f.render_widget(Paragraph::new(search), search_list[0]);
+
+        let matches: Vec<&HistoryEntry> = self.matches.iter().map(|&i| &self.entries[i]).collect();
+        let max_state_len = matches.iter().map(|e| e.state().len()).max().unwrap_or(0);
+        let max_id_len = matches.iter().map(|e| e.id.len()).max().unwrap_or(0);
+        let items: Vec<ListItem> = matches
+            .iter()
+            .map(|e| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{:<max$.max$}", e.state(), max = max_state_len)),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:<max$.max$}", e.id, max = max_id_len),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        local_time(&e.first_seen, "%m-%d %H:%M"),
+                        Style::default().fg(Color::Blue),
+                    ),
+                    Span::raw(" "),
+                    Span::raw(e.name.as_str()),
+                ]))
+            })
+            .collect();
+        let list =
+            List::new(items).highlight_style(Style::default().bg(Color::Green).fg(Color::Black));
+        f.render_stateful_widget(list, search_list[1], &mut self.list_state);
+        self.list_height = search_list[1].height;
+
+        let label = |s: &'static str| Span::styled(s, Style::default().fg(Color::Yellow));
+        let mut lines = Vec::new();
+        if let Some(e) = &self.error {
+            lines.push(Line::styled(e.as_str(), Style::default().fg(Color::Red)));
+        }
+        // the fields rather than `selected()`, so that the scroll state can still be updated
+        let selected = self.list_state.selected().and_then(|i| self.matches.get(i));
+        if let Some(e) = selected.map(|&i| &self.entries[i]) {
+            let path = |p: &Option<std::path::PathBuf>| {
+                p.as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default()
+            };
+            for (name, value) in [
+                ("Name     ", e.name.clone()),
+                ("User     ", e.user.clone()),
+                ("Partition", e.partition.clone()),
+                ("Nodes    ", e.nodelist.clone()),
+                ("TRES     ", e.tres.clone()),
+                ("Command  ", e.command.clone()),
+                ("stdout   ", path(&e.stdout)),
+                ("stderr   ", path(&e.stderr)),
+            ] {
+                lines.push(Line::from(vec![
+                    label(name),
+                    Span::raw(" "),
+                    Span::raw(value),
+                ]));
+            }
+            if let Some(report) = efficiency {
+                lines.extend(efficiency_lines(report, "Used     ", "Advice   "));
+            }
+            lines.push(Line::default());
+            for change in &e.states {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        local_time(&change.at, "%Y-%m-%d %H:%M:%S"),
+                        Style::default().fg(Color::Blue),
+                    ),
+                    Span::raw(" "),
+                    Span::raw(change.state.as_str()),
+                ]));
+            }
+            lines.push(Line::from(vec![
+                Span::styled(
+                    local_time(&e.last_seen, "%Y-%m-%d %H:%M:%S"),
+                    Style::default().fg(Color::Blue),
+                ),
+                Span::styled(" last seen", dim),
+            ]));
+            if let Some(state) = &e.final_state {
+                lines.push(Line::from(vec![
+                    label("Final    "),
+                    Span::raw(" "),
+                    Span::raw(state.as_str()),
+                ]));
+            }
+        }
+        let block = Block::default()
+            .title("─Details")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_style(focus, Focus::HistoryDetails));
+        let inner = block.inner(master_detail[1]);
+        // the lines as wrapped
+        let height = lines
+            .iter()
+            .map(|l| l.width().div_ceil(inner.width.max(1) as usize).max(1))
+            .sum();
+        let offset = self.details_scroll.update(height, inner.height);
+        let details = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((offset, 0))
+            .block(block);
+        f.render_widget(details, master_detail[1]);
+    }
+}
@@ -0,0 +1,50 @@
+use std::{io, thread};
+
+use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
+use crossterm::event::{self, Event};
+
+struct InputReader {
+    app: Sender<io::Result<Event>>,
+    handled: Receiver<()>,
+}
+
+/// Reads terminal events on a separate thread, one at a time.
+pub struct InputHandle {
+    pub receiver: Receiver<io::Result<Event>>,
+    handled: Sender<()>,
+}
+
+impl InputReader {
+    fn run(&mut self) {
+        loop {
+            let event = event::read();
+            if self.app.send(event).is_err() {
+                return;
+            }
+            // the event may start a child process in the foreground, which then gets all input
+            if self.handled.recv().is_err() {
+                return;
+            }
+        }
+    }
+}
+
+impl InputHandle {
+    pub fn new() -> Self {
+        let (app, receiver) = unbounded();
+        let (handled, handled_receiver) = bounded(1);
+        let mut actor = InputReader {
+            app,
+            handled: handled_receiver,
+        };
+        thread::spawn(move || actor.run());
+
+        Self { receiver, handled }
+    }
+
+    /// Lets the reader read the next event, once the last one is handled including any
+    /// child process it ran in the foreground.
+    pub fn handled(&self) {
+        let _ = self.handled.send(());
+    }
+}
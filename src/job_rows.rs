@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::app::Job;
+use crate::job_state::JobState;
+
+/// A line of the job list.
+#[derive(Debug, PartialEq)]
+pub enum Row {
+    /// Index into the jobs.
+    Job(usize),
+    /// The tasks of an array, listed below it while expanded.
+    Array {
+        array_id: String,
+        tasks: Vec<usize>,
+        expanded: bool,
+    },
+}
+
+impl Row {
+    /// Stays the same across refreshes, unlike the index of the row.
+    pub fn id(&self, jobs: &[Job]) -> String {
+        match self {
+            Row::Job(i) => jobs[*i].id(),
+            Row::Array { array_id, .. } => format!("{}_*", array_id),
+        }
+    }
+}
+
+/// One row per job, or in tree mode one per array, at the position of its first task.
+pub fn rows(jobs: &[Job], tree: bool, expanded: &HashSet<String>) -> Vec<Row> {
+    if !tree {
+        return (0..jobs.len()).map(Row::Job).collect();
+    }
+    let mut tasks: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, job) in jobs.iter().enumerate() {
+        if job.array_step.is_some() {
+            tasks.entry(&job.array_id).or_default().push(i);
+        }
+    }
+    let mut rows = Vec::new();
+    for (i, job) in jobs.iter().enumerate() {
+        if job.array_step.is_none() {
+            rows.push(Row::Job(i));
+            continue;
+        }
+        // the tasks follow their array, the first one adds all of them
+        let Some(tasks) = tasks.remove(job.array_id.as_str()) else {
+            continue;
+        };
+        let expanded = expanded.contains(&job.array_id);
+        rows.push(Row::Array {
+            array_id: job.array_id.clone(),
+            tasks: tasks.clone(),
+            expanded,
+        });
+        if expanded {
+            rows.extend(tasks.into_iter().map(Row::Job));
+        }
+    }
+    rows
+}
+
+/// The number of jobs per state, in the order they first appear.
+pub fn state_counts<'a>(
+    states: impl IntoIterator<Item = &'a JobState>,
+) -> Vec<(&'a JobState, usize)> {
+    let mut counts: Vec<(&JobState, usize)> = Vec::new();
+    for state in states {
+        match counts.iter_mut().find(|(s, _)| *s == state) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((state, 1)),
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(array_id: &str, array_step: Option<&str>) -> Job {
+        Job {
+            array_step: array_step.map(String::from),
+            ..Job::test(array_id)
+        }
+    }
+
+    #[test]
+    fn test_rows() {
+        let jobs = [job("7", Some("0")), job("8", None), job("7", Some("1"))];
+        assert_eq!(rows(&jobs, false, &HashSet::new()).len(), 3);
+        let collapsed = rows(&jobs, true, &HashSet::new());
+        assert_eq!(
+            collapsed,
+            [
+                Row::Array {
+                    array_id: "7".to_owned(),
+                    tasks: vec![0, 2],
+                    expanded: false,
+                },
+                Row::Job(1),
+            ]
+        );
+        assert_eq!(collapsed[0].id(&jobs), "7_*");
+        let expanded = rows(&jobs, true, &HashSet::from(["7".to_owned()]));
+        assert_eq!(expanded[1..], [Row::Job(0), Row::Job(2), Row::Job(1)]);
+
+        let states = [JobState::Running, JobState::Pending, JobState::Running];
+        assert_eq!(
+            state_counts(&states),
+            [(&JobState::Running, 2), (&JobState::Pending, 1)]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::fmt;
+
+/// The state of a job as printed by `squeue`, `scontrol` or `sacct`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum JobState {
+    Pending,
+    Running,
+    Suspended,
+    Completed,
+    Cancelled,
+    Failed,
+    Timeout,
+    NodeFail,
+    Preempted,
+    BootFail,
+    Deadline,
+    OutOfMemory,
+    /// Slurm prints some flags in place of the base state.
+    Flag(StateFlag),
+    Unknown(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StateFlag {
+    Completing,
+    Configuring,
+    PowerUpNode,
+    Requeued,
+    RequeueFed,
+    RequeueHold,
+    Resizing,
+    ResvDelHold,
+    Revoked,
+    Signaling,
+    SpecialExit,
+    StageOut,
+    Stopped,
+    UpdateDb,
+}
+
+/// Names and compact names.
+const BASE_STATES: [(&str, &str, JobState); 12] = [
+    ("PENDING", "PD", JobState::Pending),
+    ("RUNNING", "R", JobState::Running),
+    ("SUSPENDED", "S", JobState::Suspended),
+    ("COMPLETED", "CD", JobState::Completed),
+    ("CANCELLED", "CA", JobState::Cancelled),
+    ("FAILED", "F", JobState::Failed),
+    ("TIMEOUT", "TO", JobState::Timeout),
+    ("NODE_FAIL", "NF", JobState::NodeFail),
+    ("PREEMPTED", "PR", JobState::Preempted),
+    ("BOOT_FAIL", "BF", JobState::BootFail),
+    ("DEADLINE", "DL", JobState::Deadline),
+    ("OUT_OF_MEMORY", "OOM", JobState::OutOfMemory),
+];
+
+const FLAGS: [(&str, &str, StateFlag); 14] = [
+    ("COMPLETING", "CG", StateFlag::Completing),
+    ("CONFIGURING", "CF", StateFlag::Configuring),
+    ("POWER_UP_NODE", "PU", StateFlag::PowerUpNode),
+    ("REQUEUED", "RQ", StateFlag::Requeued),
+    ("REQUEUE_FED", "RF", StateFlag::RequeueFed),
+    ("REQUEUE_HOLD", "RH", StateFlag::RequeueHold),
+    ("RESIZING", "RS", StateFlag::Resizing),
+    ("RESV_DEL_HOLD", "RD", StateFlag::ResvDelHold),
+    ("REVOKED", "RV", StateFlag::Revoked),
+    ("SIGNALING", "SI", StateFlag::Signaling),
+    ("SPECIAL_EXIT", "SE", StateFlag::SpecialExit),
+    ("STAGE_OUT", "SO", StateFlag::StageOut),
+    ("STOPPED", "ST", StateFlag::Stopped),
+    ("UPDATE_DB", "UD", StateFlag::UpdateDb),
+];
+
+impl JobState {
+    /// Parses the first word, so that `CANCELLED by 1000` from `sacct` is [`JobState::Cancelled`].
+    pub fn parse(s: &str) -> Self {
+        let word = s.split_whitespace().next().unwrap_or_default();
+        if let Some((_, _, state)) = BASE_STATES.iter().find(|(name, ..)| *name == word) {
+            return state.clone();
+        }
+        match FLAGS.iter().find(|(name, ..)| *name == word) {
+            Some((_, _, flag)) => JobState::Flag(*flag),
+            None => JobState::Unknown(s.to_owned()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.names().0
+    }
+
+    /// Like `squeue --Format StateCompact`, e.g. `CD` for completed.
+    pub fn compact(&self) -> &str {
+        self.names().1
+    }
+
+    fn names(&self) -> (&str, &str) {
+        match self {
+            JobState::Flag(flag) => FLAGS
+                .iter()
+                .find(|(.., f)| f == flag)
+                .map(|(name, compact, _)| (*name, *compact))
+                .unwrap(),
+            JobState::Unknown(s) => (s, s),
+            state => BASE_STATES
+                .iter()
+                .find(|(.., s)| s == state)
+                .map(|(name, compact, _)| (*name, *compact))
+                .unwrap(),
+        }
+    }
+
+    /// Whether the job will not run again.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobState::Completed
+                | JobState::Cancelled
+                | JobState::Failed
+                | JobState::Timeout
+                | JobState::NodeFail
+                | JobState::Preempted
+                | JobState::BootFail
+                | JobState::Deadline
+                | JobState::OutOfMemory
+                | JobState::Flag(StateFlag::Revoked)
+        )
+    }
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_state() {
+        assert_eq!(JobState::parse("RUNNING"), JobState::Running);
+        assert_eq!(JobState::parse("CANCELLED by 1000"), JobState::Cancelled);
+        assert_eq!(
+            JobState::parse("REQUEUED"),
+            JobState::Flag(StateFlag::Requeued)
+        );
+        assert_eq!(JobState::parse("NODE_FAIL").as_str(), "NODE_FAIL");
+        assert_eq!(JobState::parse("COMPLETED").compact(), "CD");
+        assert_eq!(JobState::parse("REQUEUE_HOLD").to_string(), "REQUEUE_HOLD");
+        assert_eq!(JobState::parse("WEIRD").as_str(), "WEIRD");
+        assert!(JobState::parse("OUT_OF_MEMORY").is_finished());
+        assert!(!JobState::parse("COMPLETING").is_finished());
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::form::{Field, Form};
+
+/// `(form label, scontrol key)` of the editable job fields.
+const FIELDS: [(&str, &str); 5] = [
+    ("Name", "JobName"),
+    ("Time limit", "TimeLimit"),
+    ("Partition", "Partition"),
+    ("QOS", "QOS"),
+    ("Dependency", "Dependency"),
+];
+
+/// Edit form for a job, prefilled with the values from `scontrol show job`.
+pub struct JobEdit {
+    job_id: String,
+    original: Vec<String>,
+    pub form: Form,
+}
+
+/// A `scontrol update` of the changed job fields.
+#[derive(Clone, Debug)]
+pub struct JobUpdate {
+    pub job_id: String,
+    pub changes: Vec<(String, String)>,
+}
+
+impl JobEdit {
+    pub fn new(job_id: &str, info: &HashMap<String, String>) -> Self {
+        lazy_static::lazy_static! {
+            // squeue/scontrol annotate dependencies with their state, e.g. `afterok:12(unfulfilled)`
+            static ref DEPENDENCY_STATE: Regex = Regex::new(r"\([^)]*\)").unwrap();
+        }
+
+        let original: Vec<String> = FIELDS
+            .iter()
+            .map(|(_, key)| {
+                let value = info.get(*key).map(String::as_str).unwrap_or_default();
+                match *key {
+                    "Dependency" if value == "(null)" => String::new(),
+                    "Dependency" => DEPENDENCY_STATE.replace_all(value, "").into_owned(),
+                    _ => value.to_owned(),
+                }
+            })
+            .collect();
+        let form = Form::new(
+            FIELDS
+                .iter()
+                .zip(&original)
+                .map(|((label, _), value)| Field::text(*label, value))
+                .collect(),
+        );
+        Self {
+            job_id: job_id.to_owned(),
+            original,
+            form,
+        }
+    }
+
+    pub fn update(&self) -> Result<JobUpdate, String> {
+        let mut changes = Vec::new();
+        for ((label, key), original) in FIELDS.iter().zip(&self.original) {
+            let value = self.form.text(label);
+            if value == original {
+                continue;
+            }
+            match *key {
+                "TimeLimit" => validate_time_limit(value)?,
+                "Dependency" => validate_dependency(value)?,
+                "JobName" | "Partition" | "QOS" => {
+                    if value.is_empty() {
+                        return Err(format!("{} must not be empty", label));
+                    }
+                    if *key != "JobName" && value.contains(char::is_whitespace) {
+                        return Err(format!("{} must not contain spaces", label));
+                    }
+                }
+                _ => unreachable!(),
+            }
+            changes.push((key.to_string(), value.to_owned()));
+        }
+        if changes.is_empty() {
+            return Err("Nothing changed".to_string());
+        }
+        Ok(JobUpdate {
+            job_id: self.job_id.clone(),
+            changes,
+        })
+    }
+}
+
+impl JobUpdate {
+    pub fn args(&self) -> Vec<String> {
+        ["update".to_string(), format!("JobId={}", self.job_id)]
+            .into_iter()
+            .chain(self.changes.iter().map(|(k, v)| format!("{}={}", k, v)))
+            .collect()
+    }
+
+    pub fn description(&self) -> String {
+        format!(
+            "Update job {}: {}",
+            self.job_id,
+            self.changes
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+    }
+}
+
+/// Accepts the formats listed under `--time` in `man sbatch`.
+pub fn validate_time_limit(s: &str) -> Result<(), String> {
+    lazy_static::lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^(\d+|\d+:\d{1,2}|\d+:\d{1,2}:\d{1,2}|\d+-\d{1,2}|\d+-\d{1,2}:\d{1,2}|\d+-\d{1,2}:\d{1,2}:\d{1,2})$"
+        )
+        .unwrap();
+    }
+    if RE.is_match(s) || s == "UNLIMITED" || s == "INFINITE" {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid time limit {:?}, expected e.g. 90, 4:00:00 or 1-12:00:00",
+            s
+        ))
+    }
+}
+
+/// Accepts the formats listed under `--dependency` in `man sbatch`, or an empty string.
+pub fn validate_dependency(s: &str) -> Result<(), String> {
+    lazy_static::lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"^(singleton|(after|afterany|afterok|afternotok|aftercorr|afterburstbuffer)(:\d+(_(\d+|\*))?(\+\d+)?)+)$"
+        )
+        .unwrap();
+    }
+    if s.is_empty() || s.split([',', '?']).all(|d| RE.is_match(d)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid dependency {:?}, expected e.g. afterok:123:124 or singleton",
+            s
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_edit() {
+        let info = HashMap::from([
+            ("JobName".to_string(), "train".to_string()),
+            ("TimeLimit".to_string(), "1-00:00:00".to_string()),
+            ("Partition".to_string(), "gpu".to_string()),
+            ("QOS".to_string(), "normal".to_string()),
+            (
+                "Dependency".to_string(),
+                "afterok:122(unfulfilled)".to_string(),
+            ),
+        ]);
+        let edit = JobEdit::new("123", &info);
+        assert_eq!(edit.form.text("Dependency"), "afterok:122");
+        assert!(edit.update().is_err()); // nothing changed
+
+        let mut edit = JobEdit::new("123", &HashMap::new());
+        edit.form.fields[1] = Field::text("Time limit", "2-00:00:00");
+        let update = edit.update().unwrap();
+        assert_eq!(
+            update.args(),
+            vec!["update", "JobId=123", "TimeLimit=2-00:00:00"]
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        for ok in [
+            "90",
+            "90:30",
+            "4:00:00",
+            "1-12",
+            "1-12:30",
+            "1-12:30:00",
+            "UNLIMITED",
+        ] {
+            assert!(validate_time_limit(ok).is_ok(), "{}", ok);
+        }
+        for err in ["", "1h", "4:00:00:00", "-1"] {
+            assert!(validate_time_limit(err).is_err(), "{}", err);
+        }
+        for ok in [
+            "",
+            "singleton",
+            "afterok:1:2",
+            "afterany:1_*,afterok:3+10",
+            "after:1?singleton",
+        ] {
+            assert!(validate_dependency(ok).is_ok(), "{}", ok);
+        }
+        for err in ["afterok", "afterok:", "before:1", "afterok:1,"] {
+            assert!(validate_dependency(err).is_err(), "{}", err);
+        }
+    }
+}
@@ -1,73 +1,91 @@
 use std::path::PathBuf;
 use std::{io::BufRead, process::Command, thread, time::Duration};
 
-use crossbeam::channel::Sender;
+use crossbeam::{
+    channel::{Receiver, Sender, unbounded},
+    select,
+};
 use regex::Regex;
 
 use crate::app::AppMessage;
 use crate::app::Job;
+use crate::dependency;
+use crate::job_state::JobState;
+use crate::slurm::{self, SlurmDuration};
+use crate::tres::Tres;
+
+const FIELDS: [&str; 24] = [
+    "jobid",
+    "name",
+    "state",
+    "username",
+    "timeused",
+    "StartTime",
+    "tres-alloc",
+    "partition",
+    "nodelist",
+    "stdout",
+    "stderr",
+    "command",
+    "statecompact",
+    "reason",
+    "ArrayJobID",  // %A
+    "ArrayTaskID", // %a
+    "NodeList",    // %N
+    "WorkDir",     // for fallback
+    "account",
+    "qos",
+    "SubmitTime",
+    "EndTime",
+    "timelimit",
+    "dependency",
+];
 
 struct JobWatcher {
     app: Sender<AppMessage>,
+    receiver: Receiver<JobWatcherMessage>,
     interval: Duration,
     squeue_args: Vec<String>,
 }
 
-pub struct JobWatcherHandle {}
+pub enum JobWatcherMessage {
+    Refresh,
+}
+
+pub struct JobWatcherHandle {
+    sender: Sender<JobWatcherMessage>,
+}
 
 impl JobWatcher {
-    fn new(app: Sender<AppMessage>, interval: Duration, squeue_args: Vec<String>) -> Self {
+    fn new(
+        app: Sender<AppMessage>,
+        receiver: Receiver<JobWatcherMessage>,
+        interval: Duration,
+        squeue_args: Vec<String>,
+    ) -> Self {
         Self {
             app,
+            receiver,
             interval,
             squeue_args,
         }
     }
 
-    fn run(&mut self) -> Self {
-        let output_separator = "###turm###";
-        let fields = [
-            "jobid",
-            "name",
-            "state",
-            "username",
-            "timeused",
-            "StartTime",
-            "tres-alloc",
-            "partition",
-            "nodelist",
-            "stdout",
-            "stderr",
-            "command",
-            "statecompact",
-            "reason",
-            "ArrayJobID",  // %A
-            "ArrayTaskID", // %a
-            "NodeList",    // %N
-            "WorkDir",     // for fallback
-        ];
-        let output_format = fields
-            .map(|s| s.to_owned() + ":" + output_separator)
-            .join(",");
-
+    fn run(&mut self) {
         loop {
             let jobs: Vec<Job> = Command::new("squeue")
                 .args(&self.squeue_args)
                 .arg("--array")
                 .arg("--noheader")
                 .arg("--Format")
-                .arg(&output_format)
+                .arg(slurm::format_arg(&FIELDS))
                 .output()
                 .expect("failed to execute process")
                 .stdout
                 .lines()
-                .map(|l| l.unwrap().trim().to_string())
+                .map(|l| l.unwrap())
                 .filter_map(|l| {
-                    let parts: Vec<_> = l.split(output_separator).collect();
-
-                    if parts.len() != fields.len() + 1 {
-                        return None;
-                    }
+                    let parts = slurm::split_format_line(&l, FIELDS.len())?;
 
                     let id = parts[0];
                     let name = parts[1];
@@ -88,6 +106,12 @@ impl JobWatcher {
                     let array_task_id = parts[15];
                     let node_list = parts[16];
                     let working_dir = parts[17];
+                    let account = parts[18];
+                    let qos = parts[19];
+                    let submit_time = parts[20];
+                    let end_time = parts[21];
+                    let time_limit = parts[22];
+                    let dependency = parts[23];
 
                     Some(Job {
                         job_id: id.to_owned(),
@@ -97,7 +121,7 @@ impl JobWatcher {
                             _ => Some(array_task_id.to_owned()),
                         },
                         name: name.to_owned(),
-                        state: state.to_owned(),
+                        state: JobState::parse(state),
                         state_compact: state_compact.to_owned(),
                         reason: if reason == "None" {
                             None
@@ -105,13 +129,20 @@ impl JobWatcher {
                             Some(reason.to_owned())
                         },
                         user: user.to_owned(),
-                        time: time.to_owned(),
-                        start_time: start_time.to_owned(),
-                        tres: tres.to_owned(),
+                        time: SlurmDuration::parse(time).unwrap_or_default(),
+                        // `UNLIMITED`, or e.g. `INVALID`
+                        time_limit: SlurmDuration::parse(time_limit),
+                        submit_time: slurm::parse_timestamp(submit_time),
+                        start_time: slurm::parse_timestamp(start_time),
+                        end_time: slurm::parse_timestamp(end_time),
+                        tres: Tres::parse(tres),
+                        dependencies: dependency::parse(dependency),
                         partition: partition.to_owned(),
+                        account: account.to_owned(),
+                        qos: qos.to_owned(),
                         nodelist: nodelist.to_owned(),
                         command: command.to_owned(),
-                        stdout: Self::resolve_path(
+                        stdout: resolve_path(
                             stdout,
                             array_job_id,
                             array_task_id,
@@ -121,7 +152,7 @@ impl JobWatcher {
                             name,
                             working_dir,
                         ),
-                        stderr: Self::resolve_path(
+                        stderr: resolve_path(
                             stderr,
                             array_job_id,
                             array_task_id,
@@ -135,80 +166,94 @@ impl JobWatcher {
                 })
                 .collect();
             self.app.send(AppMessage::Jobs(jobs)).unwrap();
-            thread::sleep(self.interval);
+            select! {
+                recv(self.receiver) -> msg => {
+                    if msg.is_err() {
+                        return;
+                    }
+                }
+                default(self.interval) => {}
+            }
         }
     }
+}
 
-    #[allow(clippy::too_many_arguments)]
-    fn resolve_path(
-        path: &str,
-        array_master: &str,
-        array_id: &str,
-        id: &str,
-        host: &str,
-        user: &str,
-        name: &str,
-        working_dir: &str,
-    ) -> Option<PathBuf> {
-        // see https://slurm.schedmd.com/sbatch.html#SECTION_%3CB%3Efilename-pattern%3C/B%3E
-        lazy_static::lazy_static! {
-            static ref RE: Regex = Regex::new(r"%(%|A|a|J|j|N|n|s|t|u|x)").unwrap();
-        }
+/// Expands the filename pattern of `--output` or `--error` of a task.
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_path(
+    path: &str,
+    array_master: &str,
+    array_id: &str,
+    id: &str,
+    host: &str,
+    user: &str,
+    name: &str,
+    working_dir: &str,
+) -> Option<PathBuf> {
+    // see https://slurm.schedmd.com/sbatch.html#SECTION_%3CB%3Efilename-pattern%3C/B%3E
+    lazy_static::lazy_static! {
+        static ref RE: Regex = Regex::new(r"%(%|A|a|J|j|N|n|s|t|u|x)").unwrap();
+    }
+
+    let mut path = path.to_owned();
+    let slurm_no_val = "4294967294";
+    let array_id = if array_id == "N/A" {
+        slurm_no_val
+    } else {
+        array_id
+    };
 
-        let mut path = path.to_owned();
-        let slurm_no_val = "4294967294";
-        let array_id = if array_id == "N/A" {
-            slurm_no_val
+    if path.is_empty() {
+        // never happens right now, because `squeue -O stdout` seems to always return something
+        path = if array_id == slurm_no_val {
+            PathBuf::from(working_dir).join("slurm-%J.out")
         } else {
-            array_id
-        };
+            PathBuf::from(working_dir).join("slurm-%A_%a.out")
+        }
+        .to_str()
+        .unwrap()
+        .to_owned();
+    };
 
-        if path.is_empty() {
-            // never happens right now, because `squeue -O stdout` seems to always return something
-            path = if array_id == slurm_no_val {
-                PathBuf::from(working_dir).join("slurm-%J.out")
-            } else {
-                PathBuf::from(working_dir).join("slurm-%A_%a.out")
-            }
-            .to_str()
-            .unwrap()
-            .to_owned();
+    for cap in RE
+        .captures_iter(&path.clone())
+        .collect::<Vec<_>>() // TODO: this is stupid, there has to be a better way to reverse the captures...
+        .iter()
+        .rev()
+    {
+        let m = cap.get(0).unwrap();
+        let replacement = match m.as_str() {
+            "%%" => "%",
+            "%A" => array_master,
+            "%a" => array_id,
+            "%J" => id,
+            "%j" => id,
+            "%N" => host.split(',').next().unwrap_or(host),
+            "%n" => "0",
+            "%s" => "batch",
+            "%t" => "0",
+            "%u" => user,
+            "%x" => name,
+            _ => unreachable!(),
         };
 
-        for cap in RE
-            .captures_iter(&path.clone())
-            .collect::<Vec<_>>() // TODO: this is stupid, there has to be a better way to reverse the captures...
-            .iter()
-            .rev()
-        {
-            let m = cap.get(0).unwrap();
-            let replacement = match m.as_str() {
-                "%%" => "%",
-                "%A" => array_master,
-                "%a" => array_id,
-                "%J" => id,
-                "%j" => id,
-                "%N" => host.split(',').next().unwrap_or(host),
-                "%n" => "0",
-                "%s" => "batch",
-                "%t" => "0",
-                "%u" => user,
-                "%x" => name,
-                _ => unreachable!(),
-            };
-
-            path.replace_range(m.range(), replacement);
-        }
-
-        Some(PathBuf::from(working_dir).join(path)) // works even if `path` is absolute
+        path.replace_range(m.range(), replacement);
     }
+
+    Some(PathBuf::from(working_dir).join(path)) // works even if `path` is absolute
 }
 
 impl JobWatcherHandle {
     pub fn new(app: Sender<AppMessage>, interval: Duration, squeue_args: Vec<String>) -> Self {
-        let mut actor = JobWatcher::new(app, interval, squeue_args);
+        let (sender, receiver) = unbounded();
+        let mut actor = JobWatcher::new(app, receiver, interval, squeue_args);
         thread::spawn(move || actor.run());
 
-        Self {}
+        Self { sender }
+    }
+
+    /// Runs squeue right away instead of waiting for the next interval.
+    pub fn refresh(&self) {
+        let _ = self.sender.send(JobWatcherMessage::Refresh);
     }
 }
@@ -1,27 +1,50 @@
+mod account_tab;
+mod account_watcher;
+mod action;
 mod app;
+mod array_matrix;
+mod dependency;
+mod efficiency;
 mod file_watcher;
+mod form;
+mod highlight;
+mod history;
+mod history_tab;
+mod input;
+mod job_rows;
+mod job_state;
+mod job_update;
 mod job_watcher;
+mod node_watcher;
+mod nodes_tab;
+mod pager;
+mod partition_watcher;
+mod partitions_tab;
+mod paths;
+mod pending;
+mod poll_watcher;
+mod scroll;
+mod signal;
+mod slurm;
 mod squeue_args;
+mod submit;
+mod template;
+mod terminal;
+mod theme;
+mod tres;
+mod usage_watcher;
 
 use app::App;
 use clap::CommandFactory;
 use clap::Parser;
 use clap::Subcommand;
 use clap_complete::{Shell, generate};
-use crossbeam::channel::{Sender, unbounded};
-use crossterm::{
-    cursor::Show,
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
-use ratatui::{
-    Terminal,
-    backend::{Backend, CrosstermBackend},
-};
+use input::InputHandle;
+use ratatui::{Terminal, backend::Backend};
 use squeue_args::SqueueArgs;
-use std::io::Write;
-use std::{io, panic, thread};
+use std::io;
+use terminal::TerminalGuard;
+use theme::Theme;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -30,10 +53,46 @@ struct Cli {
     #[arg(long, value_name = "SECONDS", default_value_t = 2)]
     slurm_refresh: u64,
 
+    /// Refresh rate for the node and partition watchers.
+    #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+    node_refresh: u64,
+
+    /// Refresh rate for the fairshare and limits of the Account tab, which `r` refreshes as well.
+    #[arg(long, value_name = "SECONDS", default_value_t = 300)]
+    account_refresh: u64,
+
+    /// Refresh rate for the resource usage of the selected running job (sstat).
+    #[arg(long, value_name = "SECONDS", default_value_t = 10)]
+    usage_refresh: u64,
+
     /// Refresh rate for the file watcher.
     #[arg(long, value_name = "SECONDS", default_value_t = 2)]
     file_refresh: u64,
 
+    /// Command to open an interactive shell in a running job, `{job_id}` is replaced by its ID.
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        default_value = "srun --jobid={job_id} --overlap --pty bash"
+    )]
+    attach_command: String,
+
+    /// Keep jobs that left the queue in the list for this long, 0 to remove them right away.
+    #[arg(long, value_name = "SECONDS", default_value_t = 300)]
+    keep_vanished: u64,
+
+    /// Keep jobs in the history for this many days after they were last seen, 0 to keep them forever.
+    #[arg(long, value_name = "DAYS", default_value_t = 90)]
+    history_days: u64,
+
+    /// Record the jobs of all users in the history, not only your own.
+    #[arg(long)]
+    history_all_users: bool,
+
+    /// Highlight running jobs that are this close to their time limit.
+    #[arg(long, value_name = "MINUTES", default_value_t = 30)]
+    limit_margin: u64,
+
     /// squeue arguments
     #[command(flatten)]
     squeue_args: SqueueArgs,
@@ -49,81 +108,73 @@ enum CliCommand {
         /// The shell to generate completion for.
         shell: Shell,
     },
+    /// Submit a job from a template, then follow it in the TUI.
+    Submit {
+        /// Template name, prompted for if omitted.
+        template: Option<String>,
+
+        /// Set a template variable instead of prompting for it.
+        #[arg(long = "set", value_name = "NAME=VALUE")]
+        set: Vec<String>,
+
+        /// Submit without asking for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
 }
 
 fn main() -> io::Result<()> {
-    let args = Cli::parse();
-    match args.command {
+    let mut args = Cli::parse();
+    let mut initial_selection = None;
+    match args.command.take() {
         Some(CliCommand::Completion { shell }) => {
             let cmd = &mut Cli::command();
             generate(shell, cmd, cmd.get_name().to_string(), &mut io::stdout());
             return Ok(());
         }
+        Some(CliCommand::Submit { template, set, yes }) => {
+            match template::run_cli(template, set, yes)? {
+                Some(job_id) => initial_selection = Some(job_id),
+                None => return Ok(()),
+            }
+        }
         None => {}
     }
 
-    install_panic_hook();
+    let theme = Theme::load()?;
 
-    let mut terminal_guard = TerminalGuard::new(io::stdout())?;
-    run_app(terminal_guard.terminal_mut(), args)
-}
+    terminal::install_panic_hook();
 
-fn install_panic_hook() {
-    let default_hook = panic::take_hook();
-    panic::set_hook(Box::new(move |panic_info| {
-        let _ = disable_raw_mode();
-        let _ = execute!(
-            io::stdout(),
-            LeaveAlternateScreen,
-            DisableMouseCapture,
-            Show
-        );
-        default_hook(panic_info);
-    }));
-}
-
-struct TerminalGuard<W: Write> {
-    terminal: Terminal<CrosstermBackend<W>>,
-}
-
-impl<W: Write> TerminalGuard<W> {
-    fn new(mut writer: W) -> io::Result<Self> {
-        enable_raw_mode()?;
-        execute!(writer, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(writer);
-        let terminal = Terminal::new(backend)?;
-        Ok(Self { terminal })
-    }
-
-    fn terminal_mut(&mut self) -> &mut Terminal<CrosstermBackend<W>> {
-        &mut self.terminal
-    }
-}
-
-impl<W: Write> Drop for TerminalGuard<W> {
-    fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let _ = execute!(
-            self.terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        );
-        let _ = self.terminal.show_cursor();
-    }
-}
-
-fn input_loop(tx: Sender<std::io::Result<Event>>) {
-    while tx.send(event::read()).is_ok() {}
+    let mut terminal_guard = TerminalGuard::new(io::stdout())?;
+    run_app(
+        terminal_guard.terminal_mut(),
+        args,
+        theme,
+        initial_selection,
+    )
 }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, args: Cli) -> io::Result<()> {
-    let (input_tx, input_rx) = unbounded();
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    args: Cli,
+    theme: Theme,
+    initial_selection: Option<String>,
+) -> io::Result<()> {
     let mut app = App::new(
-        input_rx,
+        InputHandle::new(),
         args.slurm_refresh,
+        args.node_refresh,
+        args.account_refresh,
+        args.usage_refresh,
         args.file_refresh,
         args.squeue_args.to_vec(),
+        args.attach_command,
+        args.keep_vanished,
+        args.history_days,
+        args.history_all_users,
+        args.limit_margin,
+        theme,
+        initial_selection,
     );
-    thread::spawn(move || input_loop(input_tx));
     app.run(terminal)
 }
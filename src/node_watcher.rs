@@ -0,0 +1,166 @@
+use std::{collections::HashMap, process::Command};
+
+use crate::slurm;
+
+pub struct Node {
+    pub name: String,
+    pub state: String,
+    pub partitions: Vec<String>,
+    pub cpus_alloc: u32,
+    pub cpus_idle: u32,
+    pub cpus_total: u32,
+    /// In megabytes.
+    pub memory_alloc: u64,
+    /// In megabytes.
+    pub memory_total: u64,
+    pub gres: Vec<Gres>,
+    pub features: String,
+    pub reason: String,
+}
+
+/// A generic resource of a node, like `gpu:a100`.
+pub struct Gres {
+    pub name: String,
+    pub used: u64,
+    pub total: u64,
+}
+
+const FIELDS: [&str; 10] = [
+    "NodeList",
+    "StateLong",
+    "PartitionName",
+    "CPUsState",
+    "AllocMem",
+    "Memory",
+    "Gres",
+    "GresUsed",
+    "Features",
+    "Reason",
+];
+
+/// All nodes from `sinfo`.
+pub fn nodes() -> Result<Vec<Node>, String> {
+    slurm::output(Command::new("sinfo").args([
+        "--Node",
+        "--noheader",
+        "--Format",
+        &slurm::format_arg(&FIELDS),
+    ]))
+    .map(|output| parse_nodes(&output))
+}
+
+/// `sinfo --Node` prints a line per node and partition, which are merged here.
+fn parse_nodes(output: &str) -> Vec<Node> {
+    let mut nodes: Vec<Node> = Vec::new();
+    // index of each node in `nodes`
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    for line in output.lines() {
+        let Some(parts) = slurm::split_format_line(line, FIELDS.len()) else {
+            continue;
+        };
+        if let Some(&i) = indices.get(parts[0]) {
+            nodes[i].partitions.push(parts[2].to_owned());
+            continue;
+        }
+        indices.insert(parts[0].to_owned(), nodes.len());
+        // allocated/idle/other/total
+        let cpus: Vec<u32> = parts[3]
+            .split('/')
+            .map(|n| n.parse().unwrap_or(0))
+            .collect();
+        let used = parse_gres(parts[7]);
+        let gres = parse_gres(parts[6])
+            .into_iter()
+            .map(|(name, total)| Gres {
+                used: used
+                    .iter()
+                    .filter(|(n, _)| *n == name)
+                    .map(|(_, count)| count)
+                    .sum(),
+                name,
+                total,
+            })
+            .collect();
+        nodes.push(Node {
+            name: parts[0].to_owned(),
+            state: parts[1].to_owned(),
+            partitions: vec![parts[2].to_owned()],
+            cpus_alloc: cpus.first().copied().unwrap_or(0),
+            cpus_idle: cpus.get(1).copied().unwrap_or(0),
+            cpus_total: cpus.get(3).copied().unwrap_or(0),
+            memory_alloc: parts[4].parse().unwrap_or(0),
+            memory_total: parts[5].parse().unwrap_or(0),
+            gres,
+            features: none_if_null(parts[8]).to_owned(),
+            reason: match parts[9] {
+                "none" => String::new(),
+                reason => reason.to_owned(),
+            },
+        });
+    }
+    nodes
+}
+
+fn none_if_null(s: &str) -> &str {
+    if s == "(null)" { "" } else { s }
+}
+
+/// Parses `gpu:a100:4(S:0-1),shard:8` into names and counts.
+fn parse_gres(s: &str) -> Vec<(String, u64)> {
+    slurm::split_unnested(none_if_null(s))
+        .into_iter()
+        .map(|gres| {
+            // strip socket or index details like `(S:0-1)` or `(IDX:0,2)`
+            let gres = gres.split_once('(').map_or(gres, |(g, _)| g);
+            match gres.rsplit_once(':').map(|(n, c)| (n, c.parse())) {
+                Some((name, Ok(count))) => (name.to_owned(), count),
+                _ => (gres.to_owned(), 1),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nodes() {
+        let line = |partition: &str, gres_used: &str| {
+            [
+                "gpu01",
+                "mixed",
+                partition,
+                "8/56/0/64",
+                "65536",
+                "515000",
+                "gpu:a100:8(S:0-1),shard:16",
+                gres_used,
+                "a100,ib",
+                "none",
+            ]
+            .map(|f| format!("{}{}", f, slurm::FORMAT_SEPARATOR))
+            .concat()
+        };
+        let nodes = parse_nodes(&format!(
+            "{}\n{}\n",
+            line("gpu", "gpu:a100:4(IDX:0-1,4-5),shard:0"),
+            line("preempt", "")
+        ));
+        assert_eq!(nodes.len(), 1);
+        let node = &nodes[0];
+        assert_eq!(node.partitions, vec!["gpu", "preempt"]);
+        assert_eq!(
+            (node.cpus_alloc, node.cpus_idle, node.cpus_total),
+            (8, 56, 64)
+        );
+        assert_eq!((node.memory_alloc, node.memory_total), (65536, 515000));
+        let gres: Vec<_> = node
+            .gres
+            .iter()
+            .map(|g| (g.name.as_str(), g.used, g.total))
+            .collect();
+        assert_eq!(gres, vec![("gpu:a100", 4, 8), ("shard", 0, 16)]);
+        assert_eq!(node.reason, "");
+    }
+}
@@ -0,0 +1,237 @@
+use std::{collections::HashMap, time::Duration};
+
+use crossbeam::channel::Sender;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::{AppMessage, Focus, Job, border_style, column_widths};
+use crate::node_watcher::{self, Node};
+use crate::poll_watcher::PollWatcherHandle;
+use crate::scroll::{self, Scroll};
+use crate::slurm;
+
+/// Nodes from `sinfo`, refreshed while the tab is shown.
+pub struct NodesTab {
+    nodes: Result<Vec<Node>, String>,
+    table_state: TableState,
+    /// Rows of the table, as last rendered.
+    table_height: u16,
+    node_jobs_scroll: Scroll,
+    /// The jobs in the queue by node, from their expanded nodelists.
+    node_jobs: HashMap<String, Vec<Job>>,
+    watcher: Option<PollWatcherHandle>,
+}
+
+impl NodesTab {
+    pub fn new() -> Self {
+        Self {
+            nodes: Ok(Vec::new()),
+            table_state: TableState::default(),
+            table_height: 0,
+            node_jobs_scroll: Scroll::default(),
+            node_jobs: HashMap::new(),
+            watcher: None,
+        }
+    }
+
+    pub fn activate(&mut self, app: Sender<AppMessage>, interval: Duration) {
+        self.watcher = Some(PollWatcherHandle::new(app, interval, || {
+            AppMessage::Nodes(node_watcher::nodes())
+        }));
+    }
+
+    pub fn deactivate(&mut self) {
+        self.watcher = None;
+    }
+
+    pub fn set_nodes(&mut self, nodes: Result<Vec<Node>, String>) {
+        let selected = self.selected().map(|n| n.name.clone());
+        self.nodes = nodes;
+        let nodes = self.nodes.as_deref().unwrap_or_default();
+        let index = selected
+            .and_then(|name| nodes.iter().position(|n| n.name == name))
+            .or((!nodes.is_empty()).then_some(0));
+        self.table_state.select(index);
+    }
+
+    /// Takes the jobs in the queue, to list those on the selected node.
+    pub fn set_jobs<'a>(&mut self, jobs: impl IntoIterator<Item = &'a Job>) {
+        self.node_jobs.clear();
+        for job in jobs {
+            for node in slurm::expand_hostlist(&job.nodelist) {
+                self.node_jobs.entry(node).or_default().push(job.clone());
+            }
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Node> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.nodes.as_deref().ok()?.get(i))
+    }
+
+    pub fn help() -> Vec<(&'static str, &'static str)> {
+        vec![("⏴/⏵", "panel"), ("⏶/⏷", "navigate")]
+    }
+
+    /// Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent, focus: Focus) -> bool {
+        match focus {
+            Focus::NodeJobs => self.node_jobs_scroll.handle_key(key),
+            _ => {
+                let consumed = scroll::select_row(&mut self.table_state, key, self.table_height);
+                if consumed {
+                    self.node_jobs_scroll.reset();
+                }
+                consumed
+            }
+        }
+    }
+
+    /// `focus` is `None` while a dialog is open.
+    pub fn render(&mut self, f: &mut Frame, area: Rect, focus: Option<Focus>) {
+        let nodes_jobs = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Percentage(35)].as_ref())
+            .split(area);
+
+        let block = Block::default()
+            .title(format!(
+                "─Nodes ({})",
+                self.nodes.as_ref().map_or(0, |n| n.len())
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_style(focus, Focus::Nodes));
+        let nodes = match &self.nodes {
+            Ok(nodes) => nodes,
+            Err(e) => {
+                let error =
+                    Paragraph::new(Line::styled(e.as_str(), Style::default().fg(Color::Red)))
+                        .block(block);
+                f.render_widget(error, area);
+                return;
+            }
+        };
+        let header = [
+            "Node",
+            "State",
+            "CPUs A/I/T",
+            "Memory A/T",
+            "GRES used/total",
+            "Features",
+            "Partitions",
+            "Reason",
+        ];
+        let cells: Vec<[String; 8]> = nodes
+            .iter()
+            .map(|n| {
+                [
+                    n.name.clone(),
+                    n.state.clone(),
+                    format!("{}/{}/{}", n.cpus_alloc, n.cpus_idle, n.cpus_total),
+                    format!(
+                        "{}/{}",
+                        format_megabytes(n.memory_alloc),
+                        format_megabytes(n.memory_total)
+                    ),
+                    n.gres
+                        .iter()
+                        .map(|g| format!("{} {}/{}", g.name, g.used, g.total))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    n.features.clone(),
+                    n.partitions.join(","),
+                    n.reason.clone(),
+                ]
+            })
+            .collect();
+        let widths = column_widths(&header, &cells);
+        let rows = cells.into_iter().map(|row| {
+            Row::new(row.into_iter().enumerate().map(|(i, cell)| match i {
+                0 => Line::styled(cell, Style::default().fg(Color::Yellow)),
+                6 => Line::styled(cell, Style::default().fg(Color::Blue)),
+                _ => Line::raw(cell),
+            }))
+        });
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .row_highlight_style(Style::default().bg(Color::Green).fg(Color::Black))
+            .block(block);
+        f.render_stateful_widget(table, nodes_jobs[0], &mut self.table_state);
+        // borders and header
+        self.table_height = nodes_jobs[0].height.saturating_sub(3);
+
+        let (title, node_jobs) = match self.selected() {
+            Some(node) => {
+                let node_jobs: Vec<&Job> = self
+                    .node_jobs
+                    .get(&node.name)
+                    .map_or(Vec::new(), |jobs| jobs.iter().collect());
+                (
+                    format!("─Jobs on {} ({})", node.name, node_jobs.len()),
+                    node_jobs,
+                )
+            }
+            None => ("─Jobs".to_owned(), Vec::new()),
+        };
+        let max_id_len = node_jobs.iter().map(|j| j.id().len()).max().unwrap_or(0);
+        let max_user_len = node_jobs.iter().map(|j| j.user.len()).max().unwrap_or(0);
+        let max_time_len = node_jobs
+            .iter()
+            .map(|j| j.time.to_string().len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = node_jobs
+            .iter()
+            .map(|j| {
+                Line::from(vec![
+                    Span::raw(format!("{:<2}", j.state_compact)),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:<max$}", j.id(), max = max_id_len),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:<max$}", j.user, max = max_user_len),
+                        Style::default().fg(Color::Green),
+                    ),
+                    Span::raw(" "),
+                    Span::styled(
+                        format!("{:>max$}", j.time.to_string(), max = max_time_len),
+                        Style::default().fg(Color::Red),
+                    ),
+                    Span::raw(" "),
+                    Span::raw(j.name.as_str()),
+                ])
+            })
+            .collect();
+        let offset = self
+            .node_jobs_scroll
+            .update(lines.len(), nodes_jobs[1].height.saturating_sub(2));
+        let jobs = Paragraph::new(lines).scroll((offset, 0)).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(border_style(focus, Focus::NodeJobs)),
+        );
+        f.render_widget(jobs, nodes_jobs[1]);
+    }
+}
+
+/// E.g. `512M` or `64G`.
+fn format_megabytes(megabytes: u64) -> String {
+    if megabytes >= 1024 {
+        format!("{}G", megabytes / 1024)
+    } else {
+        format!("{}M", megabytes)
+    }
+}
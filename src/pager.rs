@@ -0,0 +1,112 @@
+use std::{env, path::Path, process::Command};
+
+#[derive(Clone, Copy)]
+pub enum Viewer {
+    Pager,
+    Editor,
+}
+
+/// Line of the file to open at, mirroring the scroll position of the log view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Position {
+    /// The line is the first one shown.
+    Top(usize),
+    /// The line is the last one shown.
+    Bottom(usize),
+    /// Following the end of the file, which is at the given line.
+    End(usize),
+}
+
+impl Position {
+    /// Maps the log view's `offset` (in displayed lines from the top or bottom) to a line of the file.
+    /// The log view splits lines at `\r` as well, so several displayed lines may belong to one line.
+    pub fn from_view(content: &str, from_bottom: bool, offset: usize) -> Self {
+        let content = content
+            .rsplit_once(['\r', '\n'])
+            .map_or(content, |(c, _)| c);
+        let lines: Vec<usize> = content
+            .lines()
+            .enumerate()
+            .flat_map(|(i, l)| l.split('\r').map(move |_| i + 1))
+            .collect();
+        let last = lines.last().copied().unwrap_or(1);
+        match (from_bottom, offset) {
+            (true, 0) => Position::End(last),
+            (true, offset) => {
+                Position::Bottom(lines.len().checked_sub(offset + 1).map_or(1, |i| lines[i]))
+            }
+            (false, offset) => Position::Top(lines.get(offset).copied().unwrap_or(last)),
+        }
+    }
+}
+
+/// `$PAGER`, or `$VISUAL`/`$EDITOR` respectively.
+pub fn program(viewer: Viewer) -> String {
+    let (vars, default): (&[&str], _) = match viewer {
+        Viewer::Pager => (&["PAGER"], "less"),
+        Viewer::Editor => (&["VISUAL", "EDITOR"], "vi"),
+    };
+    vars.iter()
+        .filter_map(|v| env::var(v).ok())
+        .find(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| default.to_owned())
+}
+
+/// Opens the file with `program`, which may include arguments like `less -R`.
+pub fn command(program: &str, path: &Path, position: Position) -> Command {
+    let mut command = Command::new("sh");
+    // pass the arguments as positional parameters, so they need no quoting
+    command
+        .arg("-c")
+        .arg(format!("{} \"$@\"", program))
+        .arg("sh")
+        .args(position_args(program, position))
+        .arg(path);
+    command
+}
+
+/// Command line arguments to open at the position, for programs that are known to support it.
+fn position_args(program: &str, position: Position) -> Vec<String> {
+    let name = program
+        .split_whitespace()
+        .next()
+        .and_then(|p| Path::new(p).file_name())
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    match (name, position) {
+        ("less", Position::Top(line)) => vec![format!("+{}g", line)],
+        ("less", Position::Bottom(line)) => vec!["-j-1".to_owned(), format!("+{}g", line)],
+        ("less", Position::End(_)) => vec!["+G".to_owned()],
+        (
+            "more" | "most" | "vi" | "vim" | "nvim" | "view" | "nano" | "emacs" | "kak",
+            Position::Top(line) | Position::Bottom(line) | Position::End(line),
+        ) => vec![format!("+{}", line)],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let content = "a\nb\rc\nd\ne\npartial";
+        assert_eq!(Position::from_view(content, false, 0), Position::Top(1));
+        assert_eq!(Position::from_view(content, false, 2), Position::Top(2));
+        assert_eq!(Position::from_view(content, false, 3), Position::Top(3));
+        assert_eq!(Position::from_view(content, true, 0), Position::End(4));
+        assert_eq!(Position::from_view(content, true, 3), Position::Bottom(2));
+        assert_eq!(Position::from_view("", true, 1), Position::Bottom(1));
+
+        assert_eq!(
+            position_args("/usr/bin/less -R", Position::Bottom(7)),
+            vec!["-j-1", "+7g"]
+        );
+        assert_eq!(position_args("nvim", Position::End(5)), vec!["+5"]);
+        assert_eq!(
+            position_args("code --wait", Position::Top(3)),
+            Vec::<String>::new()
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, process::Command};
+
+use crate::node_watcher::{self, Node};
+use crate::slurm;
+
+pub struct Partition {
+    pub name: String,
+    /// Whether jobs go here when they do not specify a partition.
+    pub default: bool,
+    pub available: String,
+    pub time_limit: String,
+    /// Node counts by state, most common first.
+    pub node_states: Vec<(String, usize)>,
+    pub nodes: usize,
+    pub cpus_alloc: u32,
+    pub cpus_idle: u32,
+    pub cpus_total: u32,
+    pub gpus_used: u64,
+    pub gpus_total: u64,
+}
+
+const FIELDS: [&str; 3] = ["PartitionName", "Available", "Time"];
+
+/// All partitions from `sinfo`.
+pub fn partitions() -> Result<Vec<Partition>, String> {
+    // the per partition output of sinfo sums up nodes of all states, so aggregate the nodes instead
+    let output = slurm::output(Command::new("sinfo").args([
+        "--noheader",
+        "--Format",
+        &slurm::format_arg(&FIELDS),
+    ]))?;
+    Ok(summarize(&output, &node_watcher::nodes()?))
+}
+
+/// Combines the `sinfo` partition lines with the nodes in each partition.
+fn summarize(output: &str, nodes: &[Node]) -> Vec<Partition> {
+    output
+        .lines()
+        .filter_map(|l| slurm::split_format_line(l, FIELDS.len()))
+        .map(|parts| {
+            // the default partition is marked with `*`
+            let name = parts[0].trim_end_matches('*');
+            let mut partition = Partition {
+                name: name.to_owned(),
+                default: name.len() != parts[0].len(),
+                available: parts[1].to_owned(),
+                time_limit: parts[2].to_owned(),
+                node_states: Vec::new(),
+                nodes: 0,
+                cpus_alloc: 0,
+                cpus_idle: 0,
+                cpus_total: 0,
+                gpus_used: 0,
+                gpus_total: 0,
+            };
+            let mut states: HashMap<&str, usize> = HashMap::new();
+            for node in nodes
+                .iter()
+                .filter(|n| n.partitions.iter().any(|p| p == name))
+            {
+                // strip flags like `*` (not responding) or `~` (powered off)
+                let state = node
+                    .state
+                    .trim_end_matches(|c: char| !c.is_ascii_alphabetic());
+                *states.entry(state).or_default() += 1;
+                partition.nodes += 1;
+                partition.cpus_alloc += node.cpus_alloc;
+                partition.cpus_idle += node.cpus_idle;
+                partition.cpus_total += node.cpus_total;
+                for gres in node
+                    .gres
+                    .iter()
+                    .filter(|g| g.name == "gpu" || g.name.starts_with("gpu:"))
+                {
+                    partition.gpus_used += gres.used;
+                    partition.gpus_total += gres.total;
+                }
+            }
+            partition.node_states = states
+                .into_iter()
+                .map(|(state, count)| (state.to_owned(), count))
+                .collect();
+            partition
+                .node_states
+                .sort_by(|a, b| (b.1, &a.0).cmp(&(a.1, &b.0)));
+            partition
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_watcher::Gres;
+
+    fn node(state: &str, partitions: &[&str], cpus_alloc: u32, gpus_used: u64) -> Node {
+        Node {
+            name: String::new(),
+            state: state.to_owned(),
+            partitions: partitions.iter().map(|p| p.to_string()).collect(),
+            cpus_alloc,
+            cpus_idle: 64 - cpus_alloc,
+            cpus_total: 64,
+            memory_alloc: 0,
+            memory_total: 0,
+            gres: vec![Gres {
+                name: "gpu:a100".to_owned(),
+                used: gpus_used,
+                total: 4,
+            }],
+            features: String::new(),
+            reason: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let output = ["gpu*", "up", "2-00:00:00"]
+            .map(|f| format!("{}{}", f, slurm::FORMAT_SEPARATOR))
+            .concat();
+        let nodes = [
+            node("mixed", &["gpu"], 8, 2),
+            node("idle~", &["gpu", "cpu"], 0, 0),
+            node("idle", &["gpu"], 0, 0),
+            node("allocated", &["cpu"], 64, 4),
+        ];
+        let partitions = summarize(&output, &nodes);
+        assert_eq!(partitions.len(), 1);
+        let p = &partitions[0];
+        assert_eq!((p.name.as_str(), p.default), ("gpu", true));
+        assert_eq!(
+            p.node_states,
+            vec![("idle".to_owned(), 2), ("mixed".to_owned(), 1)]
+        );
+        assert_eq!((p.cpus_alloc, p.cpus_idle, p.cpus_total), (8, 184, 192));
+        assert_eq!((p.gpus_used, p.gpus_total), (2, 12));
+    }
+}
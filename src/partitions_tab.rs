@@ -0,0 +1,162 @@
+use std::{collections::HashMap, time::Duration};
+
+use crossbeam::channel::Sender;
+use crossterm::event::KeyEvent;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph, Row, Table, TableState},
+};
+
+use crate::app::{AppMessage, Focus, Job, border_style, column_widths};
+use crate::job_state::JobState;
+use crate::partition_watcher::{self, Partition};
+use crate::poll_watcher::PollWatcherHandle;
+use crate::scroll;
+
+/// Partitions from `sinfo`, refreshed while the tab is shown.
+pub struct PartitionsTab {
+    partitions: Result<Vec<Partition>, String>,
+    table_state: TableState,
+    /// Rows of the table, as last rendered.
+    table_height: u16,
+    watcher: Option<PollWatcherHandle>,
+}
+
+impl PartitionsTab {
+    pub fn new() -> Self {
+        Self {
+            partitions: Ok(Vec::new()),
+            table_state: TableState::default(),
+            table_height: 0,
+            watcher: None,
+        }
+    }
+
+    pub fn activate(&mut self, app: Sender<AppMessage>, interval: Duration) {
+        self.watcher = Some(PollWatcherHandle::new(app, interval, || {
+            AppMessage::Partitions(partition_watcher::partitions())
+        }));
+    }
+
+    pub fn deactivate(&mut self) {
+        self.watcher = None;
+    }
+
+    pub fn set_partitions(&mut self, partitions: Result<Vec<Partition>, String>) {
+        let selected = self.selected().map(|p| p.name.clone());
+        self.partitions = partitions;
+        let partitions = self.partitions.as_deref().unwrap_or_default();
+        let index = selected
+            .and_then(|name| partitions.iter().position(|p| p.name == name))
+            .or((!partitions.is_empty()).then_some(0));
+        self.table_state.select(index);
+    }
+
+    fn selected(&self) -> Option<&Partition> {
+        self.table_state
+            .selected()
+            .and_then(|i| self.partitions.as_deref().ok()?.get(i))
+    }
+
+    pub fn help() -> Vec<(&'static str, &'static str)> {
+        vec![("⏶/⏷", "navigate")]
+    }
+
+    /// Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        scroll::select_row(&mut self.table_state, key, self.table_height)
+    }
+
+    /// `jobs` is the current job snapshot, which gives the running and pending jobs per partition.
+    /// `focus` is `None` while a dialog is open.
+    pub fn render(&mut self, f: &mut Frame, area: Rect, focus: Option<Focus>, jobs: &[&Job]) {
+        let block = Block::default()
+            .title(format!(
+                "─Partitions ({})",
+                self.partitions.as_ref().map_or(0, |p| p.len())
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(border_style(focus, Focus::Partitions));
+        let partitions = match &self.partitions {
+            Ok(partitions) => partitions,
+            Err(e) => {
+                let error =
+                    Paragraph::new(Line::styled(e.as_str(), Style::default().fg(Color::Red)))
+                        .block(block);
+                f.render_widget(error, area);
+                return;
+            }
+        };
+        // pending jobs may list several partitions
+        let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+        for job in jobs {
+            for partition in job.partition.split(',') {
+                let (running, pending) = counts.entry(partition).or_default();
+                match job.state {
+                    JobState::Running => *running += 1,
+                    JobState::Pending => *pending += 1,
+                    _ => {}
+                }
+            }
+        }
+        let header = [
+            "Partition",
+            "Avail",
+            "Time limit",
+            "Nodes",
+            "CPUs A/I/T",
+            "GPUs used/total",
+            "Running",
+            "Pending",
+            "Node states",
+        ];
+        let cells: Vec<[String; 9]> = partitions
+            .iter()
+            .map(|p| {
+                let (running, pending) = counts.get(p.name.as_str()).copied().unwrap_or_default();
+                [
+                    if p.default {
+                        format!("{}*", p.name)
+                    } else {
+                        p.name.clone()
+                    },
+                    p.available.clone(),
+                    p.time_limit.clone(),
+                    p.nodes.to_string(),
+                    format!("{}/{}/{}", p.cpus_alloc, p.cpus_idle, p.cpus_total),
+                    if p.gpus_total > 0 {
+                        format!("{}/{}", p.gpus_used, p.gpus_total)
+                    } else {
+                        String::new()
+                    },
+                    running.to_string(),
+                    pending.to_string(),
+                    p.node_states
+                        .iter()
+                        .map(|(state, count)| format!("{} {}", count, state))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ]
+            })
+            .collect();
+        let widths = column_widths(&header, &cells);
+        let rows = cells.into_iter().map(|row| {
+            Row::new(row.into_iter().enumerate().map(|(i, cell)| match i {
+                0 => Line::styled(cell, Style::default().fg(Color::Blue)),
+                3..=7 => Line::raw(cell).right_aligned(),
+                _ => Line::raw(cell),
+            }))
+        });
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+            .row_highlight_style(Style::default().bg(Color::Green).fg(Color::Black))
+            .block(block);
+        f.render_stateful_widget(table, area, &mut self.table_state);
+        // borders and header
+        self.table_height = area.height.saturating_sub(3);
+    }
+}
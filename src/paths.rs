@@ -0,0 +1,18 @@
+use std::{env, path::PathBuf};
+
+/// `$XDG_CONFIG_HOME/turm`, defaulting to `~/.config/turm`.
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config").join("turm")
+}
+
+/// `$XDG_STATE_HOME/turm`, defaulting to `~/.local/state/turm`.
+pub fn state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", ".local/state").join("turm")
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(fallback))
+}
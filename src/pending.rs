@@ -0,0 +1,470 @@
+use std::process::Command;
+
+use crate::app::Job;
+use crate::slurm;
+
+/// What a pending reason means and what can be done about it.
+pub struct Explanation {
+    pub meaning: String,
+    pub advice: &'static str,
+}
+
+const CATALOG: &[(&str, &str, &str)] = &[
+    (
+        "Priority",
+        "Jobs with a higher priority are ahead of it in the queue.",
+        "Waiting helps, the priority grows with age. A less busy partition may start it sooner.",
+    ),
+    (
+        "Resources",
+        "It is next in line, but the requested resources are not free yet.",
+        "Requesting fewer resources or a shorter time limit may let it start sooner.",
+    ),
+    (
+        "Dependency",
+        "It waits for the jobs it depends on.",
+        "It starts once the dependencies are satisfied.",
+    ),
+    (
+        "DependencyNeverSatisfied",
+        "A dependency can never be satisfied, e.g. afterok on a job that failed.",
+        "Cancel it, or remove the dependency with `scontrol update job`.",
+    ),
+    (
+        "BeginTime",
+        "Its earliest start time has not been reached.",
+        "Change it with `scontrol update job <id> StartTime=now`.",
+    ),
+    (
+        "ReqNodeNotAvail",
+        "Nodes it needs are down, drained or reserved, e.g. for maintenance.",
+        "Drop node constraints like --nodelist, or wait for the nodes to return.",
+    ),
+    (
+        "Reservation",
+        "It waits for its advanced reservation to become active.",
+        "It starts within the reservation window.",
+    ),
+    (
+        "JobHeldUser",
+        "It was held by its owner.",
+        "Release it with `scontrol release <id>`.",
+    ),
+    (
+        "JobHeldAdmin",
+        "It was held by an administrator.",
+        "Ask your administrators to release it.",
+    ),
+    (
+        "launch failed requeued held",
+        "Launching it failed, so it was requeued and held.",
+        "Check the nodes it ran on, then release it with `scontrol release <id>`.",
+    ),
+    (
+        "PartitionDown",
+        "Its partition is down.",
+        "Wait for the partition to come back, or move it to another one.",
+    ),
+    (
+        "PartitionInactive",
+        "Its partition is inactive and does not start jobs.",
+        "Move it to another partition.",
+    ),
+    (
+        "PartitionTimeLimit",
+        "Its time limit exceeds the limit of the partition.",
+        "Lower its time limit, or move it to a partition that allows longer jobs.",
+    ),
+    (
+        "PartitionNodeLimit",
+        "It requests more or fewer nodes than the partition allows.",
+        "Adjust the node count, or move it to another partition.",
+    ),
+    (
+        "PartitionConfig",
+        "It requests more resources than the partition has.",
+        "Request fewer resources, or move it to another partition.",
+    ),
+    (
+        "BadConstraints",
+        "No node satisfies its constraints.",
+        "Check --constraint, --gres and the memory and CPU requests against the nodes.",
+    ),
+    (
+        "NodeDown",
+        "A node it needs is down.",
+        "Wait for the node to return, or drop the node constraints.",
+    ),
+    (
+        "Licenses",
+        "The licenses it requests are in use.",
+        "It starts once enough licenses are free.",
+    ),
+    (
+        "JobArrayTaskLimit",
+        "Its array runs as many tasks at once as the `%` limit allows.",
+        "Raise the limit with `scontrol update job <id> ArrayTaskThrottle=<n>`.",
+    ),
+    (
+        "InvalidAccount",
+        "Its account does not exist or it may not use it.",
+        "Cancel it and submit with a valid --account.",
+    ),
+    (
+        "InvalidQOS",
+        "Its QOS does not exist or it may not use it.",
+        "Cancel it and submit with a valid --qos.",
+    ),
+    (
+        "Cleaning",
+        "It was requeued and its previous run is still being cleaned up.",
+        "It becomes eligible again shortly.",
+    ),
+];
+
+/// The scope of a limit from a reason like `QOSMaxGRESPerUser` or `AssocGrpCPUMinutesLimit`.
+#[derive(Debug, PartialEq)]
+enum Scope {
+    Qos,
+    Assoc,
+}
+
+/// A limit as named by `scontrol show assoc_mgr`.
+#[derive(Debug, PartialEq)]
+struct Limit {
+    scope: Scope,
+    /// E.g. `GrpTRESMins` or `MaxJobsPU`.
+    key: String,
+    /// The TRES of a TRES limit, `gres/` for any generic resource.
+    tres: Option<&'static str>,
+}
+
+/// Splits a reason like `ReqNodeNotAvail, UnavailableNodes:node01` into its code.
+fn code(reason: &str) -> &str {
+    let reason = reason.trim_matches(['(', ')']);
+    if CATALOG.iter().any(|(c, _, _)| *c == reason) {
+        return reason;
+    }
+    reason.split([',', ' ', '(']).next().unwrap_or_default()
+}
+
+fn parse_limit(code: &str) -> Option<Limit> {
+    let (scope, rest) = if let Some(rest) = code.strip_prefix("QOS") {
+        (Scope::Qos, rest)
+    } else {
+        (Scope::Assoc, code.strip_prefix("Assoc")?)
+    };
+    let kind = &rest[..3.min(rest.len())];
+    if kind != "Grp" && kind != "Max" {
+        return None;
+    }
+    let mut rest = rest[3..].trim_end_matches("Limit");
+    let mut strip = |suffixes: &[(&str, &'static str)]| {
+        for (suffix, short) in suffixes {
+            if let Some(r) = rest.strip_suffix(suffix) {
+                rest = r;
+                return *short;
+            }
+        }
+        ""
+    };
+    let per = strip(&[
+        ("PerUser", "PU"),
+        ("PerJob", "PJ"),
+        ("PerNode", "PN"),
+        ("PerAccount", "PA"),
+    ]);
+    let minutes = strip(&[("RunMinutes", "RunMins"), ("Minutes", "Mins")]);
+    let (key, tres) = match rest {
+        "Jobs" | "SubmitJobs" | "JobsAccrue" => (format!("{}{}{}", kind, rest, per), None),
+        "Wall" | "WallDuration" if kind == "Max" => ("MaxWallPJ".to_owned(), None),
+        "Wall" | "WallDuration" => ("GrpWall".to_owned(), None),
+        resource => {
+            let tres = match resource {
+                "Cpu" | "CPU" => "cpu",
+                "Mem" | "Memory" => "mem",
+                "Node" => "node",
+                "GRES" => "gres/",
+                "Billing" => "billing",
+                "Energy" => "energy",
+                "License" => "license/",
+                _ => return None,
+            };
+            (format!("{}TRES{}{}", kind, minutes, per), Some(tres))
+        }
+    };
+    Some(Limit { scope, key, tres })
+}
+
+/// Explains a pending reason, `None` for unknown ones.
+pub fn explain(reason: &str) -> Option<Explanation> {
+    let code = code(reason);
+    if let Some((_, meaning, advice)) = CATALOG.iter().find(|(c, _, _)| *c == code) {
+        return Some(Explanation {
+            meaning: meaning.to_string(),
+            advice,
+        });
+    }
+    let limit = parse_limit(code)?;
+    let scope = match limit.scope {
+        Scope::Qos => "its QOS",
+        Scope::Assoc => "its association (user and account)",
+    };
+    let grp = limit.key.starts_with("Grp");
+    let tres = limit.tres.unwrap_or_default().trim_end_matches('/');
+    let what = match limit.key.as_str() {
+        "GrpWall" | "MaxWallPJ" => "wall time".to_owned(),
+        key if key.contains("Jobs") => "number of jobs".to_owned(),
+        key if key.contains("Mins") => format!("{}-minutes", tres),
+        _ => tres.to_owned(),
+    };
+    Some(Explanation {
+        meaning: format!(
+            "It would exceed the {} {} limit of {}.",
+            match &limit.key[limit.key.len() - 2..] {
+                _ if grp => "group",
+                "PU" => "per-user",
+                "PA" => "per-account",
+                "PJ" => "per-job",
+                "PN" => "per-node",
+                _ => "maximum",
+            },
+            what,
+            scope
+        ),
+        advice: if grp || limit.key.ends_with("PU") || limit.key.ends_with("PA") {
+            "It starts once other jobs of the same limit finish. Requesting less may help."
+        } else {
+            "It alone exceeds the limit and never starts. Request less, or ask for another QOS."
+        },
+    })
+}
+
+/// Live context for a pending job that waits for a QOS or association limit:
+/// the limit and its current usage.
+pub fn context(job: &Job) -> Result<Option<String>, String> {
+    let Some(limit) = job.reason.as_deref().map(code).and_then(parse_limit) else {
+        return Ok(None);
+    };
+    let info = slurm::show_job(&job.job_id)?;
+    let field = |key| info.get(key).map(String::as_str).unwrap_or_default();
+    let (flags, filter) = match limit.scope {
+        Scope::Qos => ("flags=qos", format!("qos={}", field("QOS"))),
+        Scope::Assoc => ("flags=assoc", format!("users={}", job.user)),
+    };
+    let output = slurm::output(Command::new("scontrol").args([
+        "show",
+        "assoc_mgr",
+        flags,
+        &filter,
+        &format!("accounts={}", field("Account")),
+    ]))?;
+    Ok(Some(
+        limit_usage(&output, &limit, &job.user, field("Account"))
+            .unwrap_or_else(|| format!("{} not found in `scontrol show assoc_mgr`", limit.key)),
+    ))
+}
+
+/// The priority of a pending job, broken down into its weighted factors.
+#[derive(Clone)]
+pub struct Priority {
+    pub priority: String,
+    pub factors: Vec<(&'static str, String)>,
+    /// Position among the pending jobs of the partition by priority, and their number.
+    /// `None` if squeue did not list the job, e.g. because it just started.
+    pub rank: Option<(usize, usize)>,
+}
+
+const SPRIO_FACTORS: [(&str, &str); 6] = [
+    ("Age", "%A"),
+    ("Fairshare", "%F"),
+    ("JobSize", "%J"),
+    ("Partition", "%P"),
+    ("QOS", "%Q"),
+    ("TRES", "%T"),
+];
+
+pub fn priority(job: &Job) -> Result<Priority, String> {
+    let format = ["%r", "%Y"]
+        .into_iter()
+        .chain(SPRIO_FACTORS.map(|(_, f)| f))
+        .collect::<Vec<_>>()
+        .join("|");
+    let output = slurm::output(Command::new("sprio").args([
+        "--jobs",
+        &job.job_id,
+        "--noheader",
+        "--format",
+        &format,
+    ]))?;
+    let (priority, factors) = parse_sprio(&output, &job.partition)
+        .ok_or_else(|| format!("Job {} not found by sprio", job.job_id))?;
+    Ok(Priority {
+        priority,
+        factors,
+        rank: rank(job).ok(),
+    })
+}
+
+/// sprio prints a line per partition the job may run in, of which the first one of
+/// `partitions` is taken.
+fn parse_sprio(output: &str, partitions: &str) -> Option<(String, Vec<(&'static str, String)>)> {
+    let rows: Vec<Vec<&str>> = output
+        .lines()
+        .map(|l| l.split('|').map(str::trim).collect::<Vec<_>>())
+        .filter(|r| r.len() == SPRIO_FACTORS.len() + 2)
+        .collect();
+    let row = partitions
+        .split(',')
+        .find_map(|p| rows.iter().find(|r| r[0] == p))
+        .or(rows.first())?;
+    let factors = SPRIO_FACTORS
+        .iter()
+        .zip(&row[2..])
+        .map(|((name, _), value)| (*name, value.to_string()))
+        .collect();
+    Some((row[1].to_owned(), factors))
+}
+
+fn rank(job: &Job) -> Result<(usize, usize), String> {
+    let output = slurm::output(Command::new("squeue").args([
+        "--partition",
+        &job.partition,
+        "--states",
+        "PENDING",
+        "--array",
+        "--noheader",
+        "--sort",
+        "-p,i",
+        "--format",
+        "%i",
+    ]))?;
+    let ids: Vec<&str> = output.lines().map(str::trim).collect();
+    let id = job.id();
+    let position = ids
+        .iter()
+        .position(|i| *i == id)
+        .ok_or_else(|| format!("{} not found among the pending jobs", id))?;
+    Ok((position + 1, ids.len()))
+}
+
+/// Finds the limit in `scontrol show assoc_mgr` output, where values read `limit(usage)`
+/// and `N` means unlimited. Per user and per account limits of a QOS are listed as
+/// `[name]=MaxJobsPU=N(2) MaxTRESPU=cpu=16(8),...`.
+fn limit_usage(output: &str, limit: &Limit, user: &str, account: &str) -> Option<String> {
+    let prefix = match &limit.key {
+        key if limit.scope == Scope::Qos && key.ends_with("PU") => Some(format!("[{}]=", user)),
+        key if limit.scope == Scope::Qos && key.ends_with("PA") => Some(format!("[{}]=", account)),
+        _ => None,
+    };
+    let value = output
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| match &prefix {
+            Some(prefix) => line.strip_prefix(prefix.as_str()),
+            None => Some(line),
+        })
+        .flat_map(str::split_whitespace)
+        .find_map(|token| token.strip_prefix(&format!("{}=", limit.key)))?;
+    let (name, value) = match limit.tres {
+        None => (limit.key.clone(), value),
+        Some(tres) => value
+            .split(',')
+            .filter_map(|t| t.split_once('='))
+            .find(|(name, value)| {
+                // of any generic resource, the one that is limited
+                *name == tres
+                    || (tres.ends_with('/') && name.starts_with(tres) && !value.starts_with('N'))
+            })
+            .map(|(name, value)| (format!("{} {}", limit.key, name), value))?,
+    };
+    let (max, usage) = match value.split_once('(') {
+        Some((max, usage)) => (max, Some(usage.trim_end_matches(')'))),
+        None => (value, None),
+    };
+    let max = if max == "N" { "unlimited" } else { max };
+    Some(match usage {
+        Some(usage) => format!("{} limit {}, in use {}", name, max, usage),
+        None => format!("{} limit {}", name, max),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limit() {
+        let limit = |scope, key: &str, tres| Limit {
+            scope,
+            key: key.to_owned(),
+            tres,
+        };
+        assert_eq!(
+            parse_limit("QOSMaxGRESPerUser"),
+            Some(limit(Scope::Qos, "MaxTRESPU", Some("gres/")))
+        );
+        assert_eq!(
+            parse_limit("AssocGrpCPUMinutesLimit"),
+            Some(limit(Scope::Assoc, "GrpTRESMins", Some("cpu")))
+        );
+        assert_eq!(
+            parse_limit("QOSMaxJobsPerUserLimit"),
+            Some(limit(Scope::Qos, "MaxJobsPU", None))
+        );
+        assert_eq!(
+            parse_limit("AssocMaxWallDurationPerJobLimit"),
+            Some(limit(Scope::Assoc, "MaxWallPJ", None))
+        );
+        assert_eq!(parse_limit("Priority"), None);
+        assert!(explain("ReqNodeNotAvail, UnavailableNodes:node01").is_some());
+        assert!(explain("(launch failed requeued held)").is_some());
+        assert!(explain("SomethingNew").is_none());
+    }
+
+    #[test]
+    fn test_parse_sprio() {
+        let output = "\
+    cpu     |      1500|       500|       900|        50|         0|         0|
+    gpu     |      2500|       500|       900|        50|      1000|         0|gres/gpu=50
+";
+        let (priority, factors) = parse_sprio(output, "gpu,cpu").unwrap();
+        assert_eq!(priority, "2500");
+        assert_eq!(factors[1], ("Fairshare", "900".to_owned()));
+        assert_eq!(factors[5], ("TRES", "gres/gpu=50".to_owned()));
+        assert_eq!(parse_sprio(output, "debug").unwrap().0, "1500");
+        assert!(parse_sprio("", "cpu").is_none());
+    }
+
+    #[test]
+    fn test_limit_usage() {
+        let output = "\
+QOS Records
+
+QOS=normal(1)
+    GrpJobs=N(4) GrpJobsAccrue=N(0) GrpSubmitJobs=N(6) GrpWall=N(33.50)
+    GrpTRES=cpu=N(24),mem=N(98304),node=N(3),gres/gpu=N(4)
+    User Limits
+      [bob]=MaxJobsPU=N(2) MaxTRESPU=cpu=N(8),gres/gpu=N(0)
+      [alice]=MaxJobsPU=10(2) MaxTRESPU=cpu=N(16),mem=N(65536),gres/gpu=4(4)
+";
+        let usage = |code| limit_usage(output, &parse_limit(code).unwrap(), "alice", "lab");
+        assert_eq!(
+            usage("QOSMaxGRESPerUser").as_deref(),
+            Some("MaxTRESPU gres/gpu limit 4, in use 4")
+        );
+        assert_eq!(
+            usage("QOSMaxJobsPerUserLimit").as_deref(),
+            Some("MaxJobsPU limit 10, in use 2")
+        );
+        assert_eq!(
+            usage("QOSGrpJobsLimit").as_deref(),
+            Some("GrpJobs limit unlimited, in use 4")
+        );
+        assert_eq!(
+            usage("QOSGrpMemLimit").as_deref(),
+            Some("GrpTRES mem limit unlimited, in use 98304")
+        );
+        assert_eq!(usage("QOSGrpBillingLimit"), None);
+    }
+}
@@ -0,0 +1,68 @@
+use std::{thread, time::Duration};
+
+use crossbeam::{
+    channel::{Receiver, Sender, unbounded},
+    select,
+};
+
+use crate::app::AppMessage;
+
+/// Runs a query right away and then at every interval, and sends its result to the app.
+struct PollWatcher {
+    app: Sender<AppMessage>,
+    receiver: Receiver<()>,
+    interval: Duration,
+    query: fn() -> AppMessage,
+}
+
+/// The watcher stops once the handle is dropped.
+pub struct PollWatcherHandle {
+    sender: Sender<()>,
+}
+
+impl PollWatcher {
+    fn new(
+        app: Sender<AppMessage>,
+        receiver: Receiver<()>,
+        interval: Duration,
+        query: fn() -> AppMessage,
+    ) -> Self {
+        Self {
+            app,
+            receiver,
+            interval,
+            query,
+        }
+    }
+
+    fn run(&mut self) {
+        loop {
+            if self.app.send((self.query)()).is_err() {
+                return;
+            }
+            select! {
+                recv(self.receiver) -> msg => {
+                    if msg.is_err() {
+                        return;
+                    }
+                }
+                default(self.interval) => {}
+            }
+        }
+    }
+}
+
+impl PollWatcherHandle {
+    pub fn new(app: Sender<AppMessage>, interval: Duration, query: fn() -> AppMessage) -> Self {
+        let (sender, receiver) = unbounded();
+        let mut actor = PollWatcher::new(app, receiver, interval, query);
+        thread::spawn(move || actor.run());
+
+        Self { sender }
+    }
+
+    /// Runs the query right away instead of waiting for the next interval.
+    pub fn refresh(&self) {
+        let _ = self.sender.send(());
+    }
+}
@@ -0,0 +1,63 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::TableState;
+
+/// The offset after a scroll key, `None` for other keys.
+/// The last of the `lines` stays at the bottom of the `height` lines that are shown.
+pub fn scroll(offset: u16, key: KeyEvent, lines: u16, height: u16) -> Option<u16> {
+    let max_offset = lines.saturating_sub(height);
+    let offset = match key.code {
+        KeyCode::Char('j') | KeyCode::Down => offset.saturating_add(1),
+        KeyCode::Char('k') | KeyCode::Up => offset.saturating_sub(1),
+        KeyCode::PageDown => offset.saturating_add(height),
+        KeyCode::PageUp => offset.saturating_sub(height),
+        KeyCode::Char('g') | KeyCode::Home => 0,
+        KeyCode::Char('G') | KeyCode::End => max_offset,
+        _ => return None,
+    };
+    Some(offset.min(max_offset))
+}
+
+/// The scroll offset of a panel, clamped to its content as last rendered.
+#[derive(Default)]
+pub struct Scroll {
+    offset: u16,
+    lines: u16,
+    height: u16,
+}
+
+impl Scroll {
+    /// Returns whether the key was consumed.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match scroll(self.offset, key, self.lines, self.height) {
+            Some(offset) => self.offset = offset,
+            None => return false,
+        }
+        true
+    }
+
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+
+    /// Records the size of the content to render and returns the offset to render it at.
+    pub fn update(&mut self, lines: usize, height: u16) -> u16 {
+        self.lines = lines.min(u16::MAX as usize) as u16;
+        self.height = height;
+        self.offset = self.offset.min(self.lines.saturating_sub(height));
+        self.offset
+    }
+}
+
+/// Moves the selection of a table with `height` visible rows. Returns whether the key was consumed.
+pub fn select_row(state: &mut TableState, key: KeyEvent, height: u16) -> bool {
+    match key.code {
+        KeyCode::Char('k') | KeyCode::Up => state.select_previous(),
+        KeyCode::Char('j') | KeyCode::Down => state.select_next(),
+        KeyCode::PageUp => state.scroll_up_by(height.max(1)),
+        KeyCode::PageDown => state.scroll_down_by(height.max(1)),
+        KeyCode::Char('g') | KeyCode::Home => state.select_first(),
+        KeyCode::Char('G') | KeyCode::End => state.select_last(),
+        _ => return false,
+    }
+    true
+}
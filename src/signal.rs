@@ -0,0 +1,138 @@
+use crate::form::{Field, Form};
+
+const SIGNALS: [&str; 7] = [
+    "none (cancel job)",
+    "TERM",
+    "INT",
+    "USR1",
+    "USR2",
+    "KILL",
+    "custom",
+];
+const SCOPES: [&str; 3] = ["job steps", "batch shell (--batch)", "everything (--full)"];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignalScope {
+    Steps,
+    Batch,
+    Full,
+}
+
+/// A `scancel` invocation, either cancelling or signaling a job.
+#[derive(Clone, Debug)]
+pub struct Scancel {
+    pub job_id: String,
+    pub signal: Option<String>,
+    pub scope: SignalScope,
+    pub step: Option<String>,
+}
+
+impl Scancel {
+    pub fn form() -> Form {
+        Form::new(vec![
+            Field::choice("Signal", &SIGNALS),
+            Field::text("Custom", ""),
+            Field::choice("Send to", &SCOPES),
+            Field::text("Step", ""),
+        ])
+    }
+
+    pub fn from_form(job_id: &str, form: &Form) -> Result<Self, String> {
+        let signal = match form.choice("Signal") {
+            0 => None,
+            i if SIGNALS[i] == "custom" => {
+                let custom = form.text("Custom").to_uppercase();
+                let custom = custom.strip_prefix("SIG").unwrap_or(&custom);
+                if custom.is_empty() || !custom.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err("Custom signal must be a signal name or number".to_string());
+                }
+                Some(custom.to_string())
+            }
+            i => Some(SIGNALS[i].to_string()),
+        };
+        let scope = match form.choice("Send to") {
+            0 => SignalScope::Steps,
+            1 => SignalScope::Batch,
+            _ => SignalScope::Full,
+        };
+        let step = match form.text("Step") {
+            "" => None,
+            s if s.chars().all(|c| c.is_ascii_alphanumeric()) => Some(s.to_string()),
+            _ => return Err("Step must be a step ID such as 0 or batch".to_string()),
+        };
+        if step.is_some() && scope != SignalScope::Steps {
+            return Err("A step cannot be combined with --batch or --full".to_string());
+        }
+        Ok(Self {
+            job_id: job_id.to_owned(),
+            signal,
+            scope,
+            step,
+        })
+    }
+
+    pub fn target(&self) -> String {
+        match &self.step {
+            Some(step) => format!("{}.{}", self.job_id, step),
+            None => self.job_id.clone(),
+        }
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(signal) = &self.signal {
+            args.push(format!("--signal={}", signal));
+        }
+        match self.scope {
+            SignalScope::Steps => {}
+            SignalScope::Batch => args.push("--batch".to_string()),
+            SignalScope::Full => args.push("--full".to_string()),
+        }
+        args.push(self.target());
+        args
+    }
+
+    pub fn description(&self) -> String {
+        let scope = match self.scope {
+            SignalScope::Steps => "",
+            SignalScope::Batch => " (batch shell)",
+            SignalScope::Full => " (all steps and batch shell)",
+        };
+        match &self.signal {
+            None => format!("Cancel job {}{}", self.target(), scope),
+            Some(signal) => format!("Send SIG{} to job {}{}", signal, self.target(), scope),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scancel_args() {
+        let cancel = Scancel {
+            job_id: "123_4".to_string(),
+            signal: None,
+            scope: SignalScope::Steps,
+            step: None,
+        };
+        assert_eq!(cancel.args(), vec!["123_4"]);
+
+        let usr1 = Scancel {
+            job_id: "123".to_string(),
+            signal: Some("USR1".to_string()),
+            scope: SignalScope::Batch,
+            step: None,
+        };
+        assert_eq!(usr1.args(), vec!["--signal=USR1", "--batch", "123"]);
+
+        let step = Scancel {
+            job_id: "123".to_string(),
+            signal: Some("INT".to_string()),
+            scope: SignalScope::Steps,
+            step: Some("0".to_string()),
+        };
+        assert_eq!(step.args(), vec!["--signal=INT", "123.0"]);
+    }
+}
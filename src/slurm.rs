@@ -0,0 +1,377 @@
+use std::{
+    collections::HashMap,
+    env, fmt,
+    io::{self, Write},
+    process::{Command, Output, Stdio},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDateTime};
+
+use crate::job_state::JobState;
+
+/// Runs the command to completion.
+/// Returns stdout on success, and stderr (or the exit status) otherwise.
+pub fn output(command: &mut Command) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    result(&program, command.output())
+}
+
+/// Like [`output`], but writes `stdin` to the standard input of the command.
+pub fn output_with_stdin(command: &mut Command, stdin: &str) -> Result<String, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    result(&program, raw_output_with_stdin(command, stdin))
+}
+
+/// Runs the command with `stdin` and captures its output, regardless of the exit status.
+pub fn raw_output_with_stdin(command: &mut Command, stdin: &str) -> io::Result<Output> {
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            let mut child_stdin = child.stdin.take().expect("stdin is piped");
+            let stdin = stdin.to_owned();
+            // write from another thread, a full stdout pipe would block us otherwise
+            let writer = thread::spawn(move || child_stdin.write_all(stdin.as_bytes()));
+            let output = child.wait_with_output();
+            writer.join().expect("stdin writer panicked")?;
+            output
+        })
+}
+
+fn result(program: &str, output: io::Result<Output>) -> Result<String, String> {
+    let output = output.map_err(|e| format!("failed to execute {}: {}", program, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else if stderr.is_empty() {
+        Err(format!("{} {}", program, output.status))
+    } else {
+        Err(stderr)
+    }
+}
+
+/// Ends every field of `squeue`/`sinfo --Format` output, whose columns are padded otherwise.
+pub const FORMAT_SEPARATOR: &str = "###turm###";
+
+/// The `--Format` argument for the fields.
+pub fn format_arg(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("{}:{}", f, FORMAT_SEPARATOR))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Splits a line of `--Format` output into its trimmed fields.
+/// Returns `None` if the line does not have the expected number of fields.
+pub fn split_format_line(line: &str, fields: usize) -> Option<Vec<&str>> {
+    let parts: Vec<&str> = line.split(FORMAT_SEPARATOR).map(str::trim).collect();
+    // the separator also ends the last field
+    (parts.len() == fields + 1).then(|| parts[..fields].to_vec())
+}
+
+/// Splits at commas that are not inside brackets or parentheses,
+/// e.g. `node[01-02],gpu1` or `gpu:a100:4(IDX:0,2),shard:8`.
+pub fn split_unnested(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Expands a Slurm hostlist like `node[01-03,05]` into the host names.
+pub fn expand_hostlist(hostlist: &str) -> Vec<String> {
+    split_unnested(hostlist)
+        .into_iter()
+        .flat_map(expand_host)
+        .collect()
+}
+
+fn expand_host(host: &str) -> Vec<String> {
+    let Some((prefix, rest)) = host.split_once('[') else {
+        return vec![host.to_owned()];
+    };
+    let Some((ranges, suffix)) = rest.split_once(']') else {
+        return vec![host.to_owned()];
+    };
+    // a host name may contain several bracket expressions, e.g. `rack[1-2]-n[1-4]`
+    let suffixes = expand_host(suffix);
+    ranges
+        .split(',')
+        .flat_map(|range| {
+            let numbers: Vec<String> = match range.split_once('-') {
+                Some((start, end)) => match (start.parse::<u64>(), end.parse::<u64>()) {
+                    // keep the zero padding of the start
+                    (Ok(s), Ok(e)) => (s..=e)
+                        .map(|i| format!("{:0width$}", i, width = start.len()))
+                        .collect(),
+                    _ => vec![range.to_owned()],
+                },
+                None => vec![range.to_owned()],
+            };
+            numbers
+        })
+        .flat_map(|n| {
+            suffixes
+                .iter()
+                .map(move |s| format!("{}{}{}", prefix, n, s))
+        })
+        .collect()
+}
+
+/// Parses a duration like `2-03:04:05`, `03:04:05` or `04:05.123`.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let (days, rest) = match s.split_once('-') {
+        Some((days, rest)) => (days.parse::<u64>().ok()?, rest),
+        None => (0, s),
+    };
+    let parts: Vec<&str> = rest.split(':').collect();
+    let (hours, minutes, seconds) = match parts[..] {
+        [h, m, s] => (h.parse::<u64>().ok()?, m.parse::<u64>().ok()?, s),
+        [m, s] => (0, m.parse::<u64>().ok()?, s),
+        _ => return None,
+    };
+    let seconds = seconds.parse::<f64>().ok()?;
+    Some(
+        Duration::from_secs(((days * 24 + hours) * 60 + minutes) * 60)
+            + Duration::from_secs_f64(seconds),
+    )
+}
+
+/// A duration as Slurm prints it, see [`parse_duration`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SlurmDuration(pub Duration);
+
+impl SlurmDuration {
+    pub fn parse(s: &str) -> Option<Self> {
+        parse_duration(s).map(Self)
+    }
+}
+
+/// Like `squeue`, e.g. `1-02:03:04`, `2:03:04` or `3:04`.
+impl fmt::Display for SlurmDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.0.as_secs();
+        let (days, hours, minutes, seconds) = (s / 86400, s / 3600 % 24, s / 60 % 60, s % 60);
+        match (days, hours) {
+            (0, 0) => write!(f, "{}:{:02}", minutes, seconds),
+            (0, _) => write!(f, "{}:{:02}:{:02}", hours, minutes, seconds),
+            _ => write!(f, "{}-{:02}:{:02}:{:02}", days, hours, minutes, seconds),
+        }
+    }
+}
+
+/// Parses a timestamp like `2024-05-01T12:00:00` in local time, `None` for e.g. `N/A` or `Unknown`.
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+        .ok()?
+        .and_local_timezone(Local)
+        .earliest()
+}
+
+/// Parses a size like `1.5G` or `1024K` into bytes, plain numbers are bytes.
+pub fn parse_size(s: &str) -> Option<u64> {
+    let (number, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    };
+    let exponent = match unit {
+        "" => 0,
+        "K" => 1,
+        "M" => 2,
+        "G" => 3,
+        "T" => 4,
+        "P" => 5,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * 1024f64.powi(exponent)) as u64)
+}
+
+/// Formats bytes like `1.5G`.
+pub fn format_size(bytes: u64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["", "K", "M", "G", "T"] {
+        if size < 1024.0 {
+            return if unit.is_empty() || size >= 10.0 {
+                format!("{:.0}{}", size, unit)
+            } else {
+                format!("{:.1}{}", size, unit)
+            };
+        }
+        size /= 1024.0;
+    }
+    format!("{:.0}P", size)
+}
+
+/// Parses `Key=Value` pairs as printed by `scontrol show`.
+/// Values may contain spaces, so a token without `=` continues the previous value.
+pub fn parse_key_values(s: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let mut last_key: Option<String> = None;
+    for token in s.split_whitespace() {
+        match token.split_once('=') {
+            Some((key, value)) if !key.is_empty() && !key.contains(['/', ':', ',']) => {
+                map.insert(key.to_owned(), value.to_owned());
+                last_key = Some(key.to_owned());
+            }
+            _ => {
+                if let Some(value) = last_key.as_ref().and_then(|k| map.get_mut(k)) {
+                    value.push(' ');
+                    value.push_str(token);
+                }
+            }
+        }
+    }
+    map
+}
+
+/// `scontrol show job` as key/value pairs.
+pub fn show_job(job_id: &str) -> Result<HashMap<String, String>, String> {
+    output(Command::new("scontrol").args(["show", "job", "--oneliner", job_id]))
+        .map(|s| parse_key_values(&s))
+}
+
+/// The batch script Slurm stored for the job.
+/// Falls back to `sacct` for jobs that already left the queue.
+pub fn batch_script(job_id: &str) -> Result<String, String> {
+    output(Command::new("scontrol").args(["write", "batch_script", job_id, "-"])).or_else(|e| {
+        let script = output(Command::new("sacct").args(["--batch-script", "--jobs", job_id]))
+            .map_err(|_| e.clone())?;
+        // strip the `Batch Script for <id>` header and the dashed line below it
+        let script = script
+            .lines()
+            .skip_while(|l| l.starts_with("Batch Script for") || l.starts_with("---"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        match script.trim() {
+            "" | "NONE" => Err(e),
+            _ => Ok(script),
+        }
+    })
+}
+
+/// `$USER`, or from `id` if it is not set.
+pub fn current_user() -> String {
+    env::var("USER")
+        .ok()
+        .filter(|u| !u.is_empty())
+        .or_else(|| output(Command::new("id").arg("-un")).ok())
+        .map(|u| u.trim().to_owned())
+        .unwrap_or_default()
+}
+
+/// The final state of a job from accounting, e.g. `COMPLETED` or `FAILED (exit code 1:0)`.
+/// Accounting lags behind the queue, so this asks again while the job is not finished there,
+/// and gives up with the last state after a while.
+pub fn final_state(job_id: &str) -> Result<String, String> {
+    const ATTEMPTS: u32 = 10;
+    for _ in 1..ATTEMPTS {
+        match accounted_state(job_id) {
+            Ok((state, _)) if !state.is_finished() => thread::sleep(Duration::from_secs(3)),
+            result => return result.map(|(_, state)| state),
+        }
+    }
+    accounted_state(job_id).map(|(_, state)| state)
+}
+
+fn accounted_state(job_id: &str) -> Result<(JobState, String), String> {
+    let row = output(Command::new("sacct").args([
+        "--jobs",
+        job_id,
+        "--allocations",
+        "--noheader",
+        "--parsable2",
+        "--format",
+        "State,ExitCode",
+    ]))?;
+    let (state, exit_code) = row
+        .lines()
+        .next()
+        .and_then(|l| l.split_once('|'))
+        .ok_or_else(|| format!("Job {} not found in accounting", job_id))?;
+    let description = match exit_code {
+        "" | "0:0" => state.to_owned(),
+        exit_code => format!("{} (exit code {})", state, exit_code),
+    };
+    Ok((JobState::parse(state), description))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_values() {
+        let map = parse_key_values(
+            "JobId=123 JobName=my job UserId=alice(1000) TimeLimit=1-00:00:00 \
+             Dependency=afterok:122(unfulfilled) TRES=cpu=8,mem=64G,node=1 \
+             Command=/home/alice/run.sh",
+        );
+        assert_eq!(map["JobId"], "123");
+        assert_eq!(map["JobName"], "my job");
+        assert_eq!(map["TimeLimit"], "1-00:00:00");
+        assert_eq!(map["Dependency"], "afterok:122(unfulfilled)");
+        assert_eq!(map["TRES"], "cpu=8,mem=64G,node=1");
+        assert_eq!(map["Command"], "/home/alice/run.sh");
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(
+            parse_duration("1-02:03:04"),
+            Some(Duration::from_secs(93784))
+        );
+        assert_eq!(
+            parse_duration("03:04.500"),
+            Some(Duration::from_millis(184500))
+        );
+        assert_eq!(parse_duration("N/A"), None);
+        for s in ["1-02:03:04", "2:03:04", "3:04", "0:00"] {
+            assert_eq!(SlurmDuration::parse(s).unwrap().to_string(), s);
+        }
+        assert!(parse_timestamp("2024-05-01T12:00:00").is_some());
+        assert!(parse_timestamp("N/A").is_none());
+        assert_eq!(parse_size("1.5K"), Some(1536));
+        assert_eq!(parse_size("2G"), Some(2 << 30));
+        assert_eq!(parse_size("123"), Some(123));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(format_size(1536), "1.5K");
+        assert_eq!(format_size(64 << 30), "64G");
+        assert_eq!(format_size(100), "100");
+    }
+
+    #[test]
+    fn test_expand_hostlist() {
+        assert_eq!(
+            expand_hostlist("node[08-10,12],gpu1"),
+            vec!["node08", "node09", "node10", "node12", "gpu1"]
+        );
+        assert_eq!(
+            expand_hostlist("r[1-2]n[1,3]"),
+            vec!["r1n1", "r1n3", "r2n1", "r2n3"]
+        );
+        assert_eq!(expand_hostlist(""), Vec::<String>::new());
+        assert_eq!(
+            split_unnested("gpu:a100:2(IDX:0,2),shard:0"),
+            vec!["gpu:a100:2(IDX:0,2)", "shard:0"]
+        );
+    }
+}
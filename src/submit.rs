@@ -0,0 +1,343 @@
+use std::{collections::HashMap, process::Command};
+
+use regex::Regex;
+
+use crate::form::{Field, Form};
+use crate::job_update::validate_time_limit;
+use crate::slurm;
+
+/// An `sbatch` submission of a script passed on stdin.
+#[derive(Clone, Debug)]
+pub struct Submission {
+    pub description: String,
+    pub script: String,
+    pub args: Vec<String>,
+}
+
+impl Submission {
+    pub fn args(&self) -> Vec<String> {
+        std::iter::once("--parsable".to_string())
+            .chain(self.args.iter().cloned())
+            .collect()
+    }
+}
+
+/// Submits the job and returns the output of `sbatch`.
+pub fn sbatch(submission: &Submission) -> Result<String, String> {
+    slurm::output_with_stdin(
+        Command::new("sbatch").args(submission.args()),
+        &submission.script,
+    )
+}
+
+/// Validates the submission with `sbatch --test-only` and returns the estimated start,
+/// e.g. `Job 123 to start at 2024-01-01T12:00:00 using 8 processors on nodes n01 in partition gpu`.
+pub fn test_only(submission: &Submission) -> Result<String, String> {
+    let output = slurm::raw_output_with_stdin(
+        Command::new("sbatch")
+            .arg("--test-only")
+            .args(&submission.args),
+        &submission.script,
+    )
+    .map_err(|e| format!("failed to execute sbatch: {}", e))?;
+    // sbatch reports the estimate on stderr
+    let stderr = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(|l| l.strip_prefix("sbatch: ").unwrap_or(l))
+        .collect::<Vec<_>>()
+        .join("\n");
+    match (output.status.success(), stderr.trim()) {
+        (true, estimate) => Ok(estimate.to_owned()),
+        (false, "") => Err(format!("sbatch {}", output.status)),
+        (false, error) => Err(error.to_owned()),
+    }
+}
+
+/// The job ID printed by `sbatch --parsable`, which may be followed by `;<cluster>`.
+pub fn submitted_job_id(output: &str) -> Option<&str> {
+    let id = output.lines().last()?.split(';').next()?.trim();
+    id.chars().all(|c| c.is_ascii_digit()).then_some(id)
+}
+
+/// The sbatch options of an existing job that are offered for editing on resubmit.
+#[derive(Debug, Default, PartialEq)]
+struct JobOptions {
+    work_dir: String,
+    time: String,
+    mem: String,
+    mem_per_cpu: bool,
+    gres: String,
+    partition: String,
+}
+
+/// Without `--mem`, Slurm reports `0`, which as `--mem=0` would request all memory of the node.
+fn requested_mem(mem: String) -> String {
+    match mem.trim_end_matches(|c: char| c.is_ascii_alphabetic()) {
+        "0" => String::new(),
+        _ => mem,
+    }
+}
+
+impl JobOptions {
+    fn from_scontrol(info: &HashMap<String, String>) -> Self {
+        let get = |key: &str| info.get(key).cloned().unwrap_or_default();
+        let (mem, mem_per_cpu) = match info.get("MinMemoryCPU") {
+            Some(mem) => (mem.clone(), true),
+            None => (get("MinMemoryNode"), false),
+        };
+        let mem = requested_mem(mem);
+        let gres = info
+            .get("TresPerNode")
+            .or(info.get("Gres"))
+            .filter(|g| *g != "(null)")
+            .map(|g| {
+                g.split(',')
+                    .map(|g| {
+                        g.strip_prefix("gres/")
+                            .or(g.strip_prefix("gres:"))
+                            .unwrap_or(g)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default();
+        Self {
+            work_dir: get("WorkDir"),
+            time: get("TimeLimit"),
+            mem,
+            mem_per_cpu,
+            gres,
+            partition: get("Partition"),
+        }
+    }
+
+    /// From `sacct -o WorkDir,Timelimit,Partition,ReqMem,ReqTRES`.
+    fn from_sacct(info: &HashMap<String, String>) -> Self {
+        let get = |key: &str| info.get(key).cloned().unwrap_or_default();
+        // older Slurm versions suffix ReqMem with `c` (per CPU) or `n` (per node)
+        let req_mem = get("ReqMem");
+        let (mem, mem_per_cpu) = match req_mem.strip_suffix('c') {
+            Some(mem) => (mem.to_owned(), true),
+            None => (req_mem.trim_end_matches('n').to_owned(), false),
+        };
+        let mem = requested_mem(mem);
+        let req_tres = get("ReqTRES");
+        let gres: Vec<(&str, &str)> = req_tres
+            .split(',')
+            .filter_map(|t| t.strip_prefix("gres/")?.split_once('='))
+            .collect();
+        // prefer typed gres (`gpu:a100=4`) over the untyped total (`gpu=4`)
+        let typed: Vec<_> = gres.iter().filter(|(n, _)| n.contains(':')).collect();
+        let gres = if typed.is_empty() {
+            gres.iter().collect()
+        } else {
+            typed
+        };
+        Self {
+            work_dir: get("WorkDir"),
+            time: match get("Timelimit").as_str() {
+                "Partition_Limit" => String::new(),
+                time => time.to_owned(),
+            },
+            mem,
+            mem_per_cpu,
+            gres: gres
+                .iter()
+                .map(|(name, count)| format!("{}:{}", name, count))
+                .collect::<Vec<_>>()
+                .join(","),
+            partition: get("Partition"),
+        }
+    }
+
+    fn fetch(job_id: &str) -> Result<Self, String> {
+        slurm::show_job(job_id)
+            .map(|info| Self::from_scontrol(&info))
+            .or_else(|e| {
+                let fields = ["WorkDir", "Timelimit", "Partition", "ReqMem", "ReqTRES"];
+                let row = slurm::output(std::process::Command::new("sacct").args([
+                    "--jobs",
+                    job_id,
+                    "--allocations",
+                    "--noheader",
+                    "--parsable2",
+                    "--format",
+                    &fields.join(","),
+                ]))
+                .map_err(|_| e.clone())?;
+                let values: Vec<&str> = row.lines().next().ok_or(e)?.split('|').collect();
+                let info = fields
+                    .iter()
+                    .zip(values)
+                    .map(|(k, v)| (k.to_string(), v.to_owned()))
+                    .collect();
+                Ok(Self::from_sacct(&info))
+            })
+    }
+}
+
+/// Resubmit form for an existing job, prefilled with its options.
+pub struct Resubmit {
+    job_id: String,
+    script: String,
+    work_dir: String,
+    mem_per_cpu: bool,
+    pub form: Form,
+}
+
+impl Resubmit {
+    /// Fetches the stored script and options of the job.
+    /// `array_task` restricts the resubmission to this task of an array job.
+    pub fn fetch(job_id: &str, array_task: Option<&str>) -> Result<Self, String> {
+        let script = slurm::batch_script(job_id)?;
+        let options = JobOptions::fetch(job_id)?;
+        Ok(Self::new(job_id, script, options, array_task))
+    }
+
+    fn new(job_id: &str, script: String, options: JobOptions, array_task: Option<&str>) -> Self {
+        let form = Form::new(vec![
+            Field::text("Time limit", &options.time),
+            Field::text(
+                if options.mem_per_cpu {
+                    "Memory per CPU"
+                } else {
+                    "Memory"
+                },
+                &options.mem,
+            ),
+            Field::text("GRES", &options.gres),
+            Field::text("Partition", &options.partition),
+            Field::text("Array", array_task.unwrap_or_default()),
+            Field::text("Directory", &options.work_dir),
+        ]);
+        Self {
+            job_id: job_id.to_owned(),
+            script,
+            work_dir: options.work_dir,
+            mem_per_cpu: options.mem_per_cpu,
+            form,
+        }
+    }
+
+    pub fn submission(&self) -> Result<Submission, String> {
+        lazy_static::lazy_static! {
+            static ref MEMORY: Regex = Regex::new(r"^\d+[KMGT]?$").unwrap();
+        }
+
+        let mut args = Vec::new();
+        let time = self.form.text("Time limit");
+        if !time.is_empty() {
+            validate_time_limit(time)?;
+            args.push(format!("--time={}", time));
+        }
+        let (mem_label, mem_option) = if self.mem_per_cpu {
+            ("Memory per CPU", "--mem-per-cpu")
+        } else {
+            ("Memory", "--mem")
+        };
+        let mem = self.form.text(mem_label);
+        if !mem.is_empty() {
+            if !MEMORY.is_match(mem) {
+                return Err(format!(
+                    "Invalid memory {:?}, expected e.g. 4000M or 64G",
+                    mem
+                ));
+            }
+            args.push(format!("{}={}", mem_option, mem));
+        }
+        for (label, option) in [
+            ("GRES", "--gres"),
+            ("Partition", "--partition"),
+            ("Array", "--array"),
+        ] {
+            let value = self.form.text(label);
+            if value.contains(char::is_whitespace) {
+                return Err(format!("{} must not contain spaces", label));
+            }
+            if !value.is_empty() {
+                args.push(format!("{}={}", option, value));
+            }
+        }
+        let work_dir = match self.form.text("Directory") {
+            "" => self.work_dir.as_str(),
+            dir => dir,
+        };
+        if !work_dir.is_empty() {
+            args.push(format!("--chdir={}", work_dir));
+        }
+        Ok(Submission {
+            description: format!("Resubmit job {}", self.job_id),
+            script: self.script.clone(),
+            args,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_job_options() {
+        let scontrol = JobOptions::from_scontrol(&map(&[
+            ("WorkDir", "/home/alice"),
+            ("TimeLimit", "1-00:00:00"),
+            ("Partition", "gpu"),
+            ("MinMemoryNode", "64G"),
+            ("TresPerNode", "gres/gpu:a100:4"),
+        ]));
+        let sacct = JobOptions::from_sacct(&map(&[
+            ("WorkDir", "/home/alice"),
+            ("Timelimit", "1-00:00:00"),
+            ("Partition", "gpu"),
+            ("ReqMem", "64Gn"),
+            (
+                "ReqTRES",
+                "billing=8,cpu=8,gres/gpu:a100=4,gres/gpu=4,mem=64G,node=1",
+            ),
+        ]));
+        let expected = JobOptions {
+            work_dir: "/home/alice".to_string(),
+            time: "1-00:00:00".to_string(),
+            mem: "64G".to_string(),
+            mem_per_cpu: false,
+            gres: "gpu:a100:4".to_string(),
+            partition: "gpu".to_string(),
+        };
+        assert_eq!(scontrol, expected);
+        assert_eq!(sacct, expected);
+        let unset = JobOptions::from_scontrol(&map(&[("MinMemoryNode", "0")]));
+        assert_eq!(unset.mem, "");
+        let unset = JobOptions::from_sacct(&map(&[("ReqMem", "0n")]));
+        assert_eq!(unset.mem, "");
+
+        let resubmit = Resubmit::new("123", "#!/bin/sh".to_string(), expected, Some("7"));
+        assert_eq!(
+            resubmit.submission().unwrap().args(),
+            vec![
+                "--parsable",
+                "--time=1-00:00:00",
+                "--mem=64G",
+                "--gres=gpu:a100:4",
+                "--partition=gpu",
+                "--array=7",
+                "--chdir=/home/alice",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_submitted_job_id() {
+        assert_eq!(submitted_job_id("12345"), Some("12345"));
+        assert_eq!(submitted_job_id("12345;cluster"), Some("12345"));
+        assert_eq!(submitted_job_id("sbatch: warning\n12345"), Some("12345"));
+        assert_eq!(submitted_job_id(""), None);
+    }
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use regex::{Captures, Regex};
+
+use crate::form::{Field, Form};
+use crate::paths;
+use crate::submit::{self, Submission};
+
+lazy_static::lazy_static! {
+    // `{{name}}` or `{{name:default}}`
+    static ref VARIABLE: Regex =
+        Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*(?::([^}]*))?\}\}").unwrap();
+}
+
+/// An sbatch script with `{{variables}}`, read from the templates directory.
+#[derive(Clone)]
+pub struct Template {
+    pub name: String,
+    pub content: String,
+}
+
+pub struct Variable {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+pub fn templates_dir() -> PathBuf {
+    paths::config_dir().join("templates")
+}
+
+impl Template {
+    /// All templates sorted by name. A missing templates directory means no templates.
+    pub fn load_all() -> io::Result<Vec<Template>> {
+        let entries = match fs::read_dir(templates_dir()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut templates = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) if !name.starts_with('.') && path.is_file() => name.to_owned(),
+                _ => continue,
+            };
+            templates.push(Template {
+                name,
+                content: fs::read_to_string(&path)?,
+            });
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(templates)
+    }
+
+    /// Variables in order of first appearance. The first default given for a variable wins.
+    pub fn variables(&self) -> Vec<Variable> {
+        let mut variables: Vec<Variable> = Vec::new();
+        for cap in VARIABLE.captures_iter(&self.content) {
+            let name = &cap[1];
+            let default = cap.get(2).map(|d| d.as_str().trim().to_owned());
+            match variables.iter_mut().find(|v| v.name == name) {
+                Some(v) => {
+                    if v.default.is_none() {
+                        v.default = default;
+                    }
+                }
+                None => variables.push(Variable {
+                    name: name.to_owned(),
+                    default,
+                }),
+            }
+        }
+        variables
+    }
+
+    /// Empty or missing values fall back to the default of the variable.
+    pub fn render(&self, values: &HashMap<String, String>) -> Result<String, String> {
+        let variables = self.variables();
+        let mut missing = Vec::new();
+        let script = VARIABLE.replace_all(&self.content, |cap: &Captures| {
+            let name = &cap[1];
+            let value = values.get(name).filter(|v| !v.is_empty()).or(variables
+                .iter()
+                .find(|v| v.name == name)
+                .and_then(|v| v.default.as_ref()));
+            match value {
+                Some(value) => value.clone(),
+                None => {
+                    if !missing.contains(&name.to_owned()) {
+                        missing.push(name.to_owned());
+                    }
+                    String::new()
+                }
+            }
+        });
+        if missing.is_empty() {
+            Ok(script.into_owned())
+        } else {
+            Err(format!("Missing value for {}", missing.join(", ")))
+        }
+    }
+
+    pub fn form(&self) -> Form {
+        Form::new(
+            self.variables()
+                .into_iter()
+                .map(|v| Field::text(v.name, v.default.as_deref().unwrap_or_default()))
+                .collect(),
+        )
+    }
+
+    pub fn render_form(&self, form: &Form) -> Result<String, String> {
+        let values = form
+            .fields
+            .iter()
+            .map(|f| (f.label.clone(), form.text(&f.label).to_owned()))
+            .collect();
+        self.render(&values)
+    }
+
+    pub fn submission(&self, script: String) -> Submission {
+        Submission {
+            description: format!("Submit template {}", self.name),
+            script,
+            args: Vec::new(),
+        }
+    }
+}
+
+fn prompt(message: &str) -> io::Result<String> {
+    print!("{}", message);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no input"));
+    }
+    Ok(line.trim().to_owned())
+}
+
+/// `turm submit`: prompts for the template and its variables, previews the script and submits it.
+/// Returns the ID of the submitted job, or `None` if the user declined to submit.
+pub fn run_cli(name: Option<String>, set: Vec<String>, yes: bool) -> io::Result<Option<String>> {
+    let templates = Template::load_all()?;
+    if templates.is_empty() {
+        return Err(io::Error::other(format!(
+            "no templates found in {}",
+            templates_dir().display()
+        )));
+    }
+    let template = match name {
+        Some(name) => templates.iter().find(|t| t.name == name).ok_or_else(|| {
+            io::Error::other(format!(
+                "no template {:?}, available: {}",
+                name,
+                templates
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?,
+        None => {
+            for (i, t) in templates.iter().enumerate() {
+                println!("{:>3}) {}", i + 1, t.name);
+            }
+            let choice = prompt("Template: ")?;
+            choice
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| templates.get(i.wrapping_sub(1)))
+                .or_else(|| templates.iter().find(|t| t.name == choice))
+                .ok_or_else(|| io::Error::other(format!("no template {:?}", choice)))?
+        }
+    };
+
+    let variables = template.variables();
+    let mut values = HashMap::new();
+    for assignment in set {
+        let (name, value) = assignment.split_once('=').ok_or_else(|| {
+            io::Error::other(format!("expected NAME=VALUE, got {:?}", assignment))
+        })?;
+        if !variables.iter().any(|v| v.name == name) {
+            return Err(io::Error::other(format!(
+                "template {:?} has no variable {:?}, available: {}",
+                template.name,
+                name,
+                variables
+                    .iter()
+                    .map(|v| v.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+        values.insert(name.to_owned(), value.to_owned());
+    }
+    for variable in variables {
+        if values.contains_key(&variable.name) {
+            continue;
+        }
+        let value = match &variable.default {
+            Some(default) => prompt(&format!("{} [{}]: ", variable.name, default))?,
+            None => prompt(&format!("{}: ", variable.name))?,
+        };
+        values.insert(variable.name, value);
+    }
+    let script = template.render(&values).map_err(io::Error::other)?;
+    let submission = template.submission(script);
+
+    println!("\n{}\n", submission.script.trim_end());
+    let estimate = submit::test_only(&submission).map_err(io::Error::other)?;
+    println!("{}", estimate);
+    if !yes && !prompt("Submit? [y/N] ")?.eq_ignore_ascii_case("y") {
+        return Ok(None);
+    }
+    let output = submit::sbatch(&submission).map_err(io::Error::other)?;
+    let job_id = submit::submitted_job_id(&output)
+        .ok_or_else(|| io::Error::other(format!("unexpected sbatch output {:?}", output)))?;
+    println!("Submitted batch job {}", job_id);
+    Ok(Some(job_id.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        let template = Template {
+            name: "train".to_string(),
+            content: "#SBATCH --gres=gpu:{{gpus:1}}\n#SBATCH -J {{ name }}\n\
+                      python train.py --name {{name}} --gpus {{gpus}}\n"
+                .to_string(),
+        };
+        let variables = template.variables();
+        assert_eq!(
+            variables
+                .iter()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["gpus", "name"]
+        );
+        assert_eq!(variables[0].default.as_deref(), Some("1"));
+        assert_eq!(variables[1].default, None);
+
+        assert_eq!(
+            template.render(&HashMap::new()),
+            Err("Missing value for name".to_string())
+        );
+        let values = HashMap::from([("name".to_string(), "run1".to_string())]);
+        assert_eq!(
+            template.render(&values).unwrap(),
+            "#SBATCH --gres=gpu:1\n#SBATCH -J run1\npython train.py --name run1 --gpus 1\n"
+        );
+    }
+}
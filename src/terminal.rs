@@ -0,0 +1,67 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Terminal,
+    backend::{Backend, CrosstermBackend},
+};
+
+fn enter<W: Write>(writer: &mut W) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(writer, EnterAlternateScreen, EnableMouseCapture)
+}
+
+/// Runs every step even if one fails, and returns the first error.
+fn leave<W: Write>(writer: &mut W) -> io::Result<()> {
+    let raw_mode = disable_raw_mode();
+    let screen = execute!(writer, LeaveAlternateScreen);
+    let mouse = execute!(writer, DisableMouseCapture);
+    let cursor = execute!(writer, Show);
+    raw_mode.and(screen).and(mouse).and(cursor)
+}
+
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let _ = leave(&mut io::stdout());
+        default_hook(panic_info);
+    }));
+}
+
+pub struct TerminalGuard<W: Write> {
+    terminal: Terminal<CrosstermBackend<W>>,
+}
+
+impl<W: Write> TerminalGuard<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        enter(&mut writer)?;
+        let backend = CrosstermBackend::new(writer);
+        let terminal = Terminal::new(backend)?;
+        Ok(Self { terminal })
+    }
+
+    pub fn terminal_mut(&mut self) -> &mut Terminal<CrosstermBackend<W>> {
+        &mut self.terminal
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        let _ = leave(self.terminal.backend_mut());
+    }
+}
+
+/// Hands the terminal to `f` (e.g. to run an interactive program) and restores the TUI afterwards.
+/// The caller has to make sure no one else reads terminal input in the meantime, see `InputHandle`.
+pub fn suspend<B: Backend, T>(terminal: &mut Terminal<B>, f: impl FnOnce() -> T) -> io::Result<T> {
+    leave(&mut io::stdout())?;
+    let result = f();
+    enter(&mut io::stdout())?;
+    terminal.clear()?;
+    Ok(result)
+}
@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fs, io, path::PathBuf, str::FromStr};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::job_state::{JobState, StateFlag};
+use crate::paths;
+
+/// Styles of the job states, overridable in `theme.toml`.
+pub struct Theme {
+    states: HashMap<String, Style>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    /// Slurm state names to styles like `bold red` or `#ff8800`.
+    #[serde(default)]
+    states: HashMap<String, String>,
+}
+
+const DEFAULT_STATES: [(&str, &str); 22] = [
+    ("PENDING", "yellow"),
+    ("RUNNING", "green"),
+    ("SUSPENDED", "magenta"),
+    ("COMPLETED", "blue"),
+    ("CANCELLED", "light-magenta"),
+    ("FAILED", "red"),
+    ("TIMEOUT", "red"),
+    ("NODE_FAIL", "red"),
+    ("PREEMPTED", "light-red"),
+    ("BOOT_FAIL", "red"),
+    ("DEADLINE", "red"),
+    ("OUT_OF_MEMORY", "red"),
+    ("COMPLETING", "dim"),
+    ("CONFIGURING", "dim green"),
+    ("STAGE_OUT", "dim"),
+    ("SIGNALING", "dim"),
+    ("REQUEUED", "cyan"),
+    ("REQUEUE_HOLD", "cyan"),
+    ("REQUEUE_FED", "cyan"),
+    ("RESV_DEL_HOLD", "yellow"),
+    ("STOPPED", "magenta"),
+    ("SPECIAL_EXIT", "red"),
+];
+
+fn theme_path() -> PathBuf {
+    paths::config_dir().join("theme.toml")
+}
+
+impl Theme {
+    /// The defaults, with the styles of the theme file if there is one.
+    pub fn load() -> io::Result<Self> {
+        let mut theme = Self::default();
+        let path = theme_path();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(theme),
+            Err(e) => return Err(e),
+        };
+        theme.merge(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })?;
+        Ok(theme)
+    }
+
+    /// Overrides the styles of the states in the content of a theme file.
+    fn merge(&mut self, content: &str) -> Result<(), String> {
+        let file: ThemeFile = toml::from_str(content).map_err(|e| e.to_string())?;
+        for (state, style) in file.states {
+            let name = state.to_uppercase();
+            let parsed = JobState::parse(&name);
+            if matches!(parsed, JobState::Unknown(_)) || parsed.as_str() != name {
+                return Err(format!("unknown job state {:?}", state));
+            }
+            self.states.insert(name, parse_style(&style)?);
+        }
+        Ok(())
+    }
+
+    pub fn state(&self, state: &JobState) -> Style {
+        self.states.get(state.as_str()).copied().unwrap_or_default()
+    }
+
+    /// Failed jobs color their whole row, the others only their state.
+    pub fn row(&self, state: &JobState) -> Option<Style> {
+        let failed = matches!(
+            state,
+            JobState::Failed
+                | JobState::Timeout
+                | JobState::NodeFail
+                | JobState::BootFail
+                | JobState::Deadline
+                | JobState::OutOfMemory
+                | JobState::Flag(StateFlag::SpecialExit)
+        );
+        failed.then(|| self.state(state))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            states: DEFAULT_STATES
+                .iter()
+                .map(|(state, style)| (state.to_string(), parse_style(style).unwrap()))
+                .collect(),
+        }
+    }
+}
+
+/// Words of modifiers and at most one color, e.g. `bold light-red` or `#ff8800`.
+fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::default();
+    for word in s.split_whitespace() {
+        style = match word {
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            "reversed" => style.add_modifier(Modifier::REVERSED),
+            color => {
+                style.fg(Color::from_str(color).map_err(|_| format!("invalid style {:?}", s))?)
+            }
+        };
+    }
+    Ok(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(
+            parse_style("bold light-red"),
+            Ok(Style::default()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD))
+        );
+        assert_eq!(
+            parse_style("#ff8800"),
+            Ok(Style::default().fg(Color::Rgb(0xff, 0x88, 0x00)))
+        );
+        assert!(parse_style("blinking").is_err());
+        let theme = Theme::default();
+        assert_eq!(theme.state(&JobState::Running).fg, Some(Color::Green));
+        assert!(theme.row(&JobState::Running).is_none());
+        assert!(theme.row(&JobState::OutOfMemory).is_some());
+        assert!(
+            theme
+                .row(&JobState::Flag(StateFlag::SpecialExit))
+                .is_some_and(|style| style.fg == Some(Color::Red))
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut theme = Theme::default();
+        assert_eq!(theme.merge("[states]\nrunning = \"bold blue\"\n"), Ok(()));
+        assert_eq!(theme.state(&JobState::Running).fg, Some(Color::Blue));
+        assert!(theme.merge("[states]\nRUNING = \"blue\"\n").is_err());
+        assert!(theme.merge("[states]\nPENDING = \"blinking\"\n").is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use std::{collections::BTreeMap, fmt, iter::Sum, ops::AddAssign};
+
+use crate::slurm;
+
+/// Trackable resources, parsed from a list like `cpu=8,mem=64G,node=1,billing=8,gres/gpu:a100=4`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tres {
+    pub cpu: u64,
+    /// In bytes.
+    pub mem: u64,
+    pub node: u64,
+    pub billing: u64,
+    /// Count per generic resource and type, e.g. `gpu` or `gpu:a100`.
+    pub gres: BTreeMap<String, u64>,
+    /// Any other TRES, e.g. licenses, as printed by Slurm.
+    pub other: BTreeMap<String, String>,
+}
+
+impl Tres {
+    pub fn parse(list: &str) -> Self {
+        let mut tres = Self::default();
+        for (name, value) in list.split(',').filter_map(|t| t.split_once('=')) {
+            match (name, value.parse::<u64>()) {
+                ("cpu", Ok(count)) => tres.cpu = count,
+                ("mem", _) => tres.mem = parse_memory(value).unwrap_or(0),
+                ("node", Ok(count)) => tres.node = count,
+                ("billing", Ok(count)) => tres.billing = count,
+                (_, Ok(count)) if name.starts_with("gres/") => {
+                    tres.gres.insert(name["gres/".len()..].to_owned(), count);
+                }
+                _ => {
+                    tres.other.insert(name.to_owned(), value.to_owned());
+                }
+            }
+        }
+        tres
+    }
+
+    /// The count of a generic resource like `gpu`, or of one type like `gpu:a100`.
+    /// Types count towards their resource unless it is listed itself.
+    pub fn gres(&self, name: &str) -> u64 {
+        match self.gres.get(name) {
+            Some(count) => *count,
+            None => self
+                .gres
+                .iter()
+                .filter(|(n, _)| n.strip_prefix(name).is_some_and(|t| t.starts_with(':')))
+                .map(|(_, count)| count)
+                .sum(),
+        }
+    }
+
+    pub fn gpus(&self) -> u64 {
+        self.gres("gpu")
+    }
+
+    /// The amount of a TRES by its Slurm name, e.g. `cpu`, `mem` (in bytes) or `gres/gpu`.
+    pub fn amount(&self, name: &str) -> f64 {
+        match name {
+            "cpu" => self.cpu as f64,
+            "mem" => self.mem as f64,
+            "node" => self.node as f64,
+            "billing" => self.billing as f64,
+            _ => match name.strip_prefix("gres/") {
+                Some(gres) => self.gres(gres) as f64,
+                None => self
+                    .other
+                    .get(name)
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0.0),
+            },
+        }
+    }
+
+    /// Readable names and amounts, e.g. `("Memory", "64G")` or `("GPU a100", "4")`.
+    pub fn table(&self) -> Vec<(String, String)> {
+        let mut columns = Vec::new();
+        if self.cpu > 0 {
+            columns.push(("CPUs".to_owned(), self.cpu.to_string()));
+        }
+        if self.mem > 0 {
+            columns.push(("Memory".to_owned(), slurm::format_size(self.mem)));
+        }
+        if self.node > 0 {
+            columns.push(("Nodes".to_owned(), self.node.to_string()));
+        }
+        for (gres, count) in &self.gres {
+            let name = match gres.split_once(':') {
+                Some(("gpu", kind)) => format!("GPU {}", kind),
+                Some((name, kind)) => format!("{} {}", name, kind),
+                None if gres == "gpu" => "GPUs".to_owned(),
+                None => gres.clone(),
+            };
+            columns.push((name, count.to_string()));
+        }
+        if self.billing > 0 {
+            columns.push(("Billing".to_owned(), self.billing.to_string()));
+        }
+        columns.extend(self.other.iter().map(|(n, v)| (n.clone(), v.clone())));
+        columns
+    }
+
+    /// E.g. `24 CPUs, 192G, 8 GPUs`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} CPUs", self.cpu), slurm::format_size(self.mem)];
+        if self.gpus() > 0 {
+            parts.push(format!("{} GPUs", self.gpus()));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Slurm sizes without a unit are megabytes.
+pub fn parse_memory(s: &str) -> Option<u64> {
+    if s.ends_with(|c: char| c.is_ascii_digit()) {
+        slurm::parse_size(&format!("{}M", s))
+    } else {
+        slurm::parse_size(s)
+    }
+}
+
+/// The largest unit the bytes are a whole multiple of, so that parsing it again is lossless.
+fn format_memory(bytes: u64) -> String {
+    for (exponent, unit) in [(4, "T"), (3, "G"), (2, "M"), (1, "K")] {
+        let size = 1u64 << (10 * exponent);
+        if bytes >= size && bytes.is_multiple_of(size) {
+            return format!("{}{}", bytes / size, unit);
+        }
+    }
+    format!("{}", bytes)
+}
+
+/// Other TRES are not added up.
+impl AddAssign<&Tres> for Tres {
+    fn add_assign(&mut self, other: &Tres) {
+        self.cpu += other.cpu;
+        self.mem += other.mem;
+        self.node += other.node;
+        self.billing += other.billing;
+        for (gres, count) in &other.gres {
+            *self.gres.entry(gres.clone()).or_default() += count;
+        }
+    }
+}
+
+impl<'a> Sum<&'a Tres> for Tres {
+    fn sum<I: Iterator<Item = &'a Tres>>(iter: I) -> Self {
+        iter.fold(Tres::default(), |mut sum, tres| {
+            sum += tres;
+            sum
+        })
+    }
+}
+
+/// In the format of Slurm.
+impl fmt::Display for Tres {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut items = Vec::new();
+        if self.cpu > 0 {
+            items.push(format!("cpu={}", self.cpu));
+        }
+        if self.mem > 0 {
+            items.push(format!("mem={}", format_memory(self.mem)));
+        }
+        if self.node > 0 {
+            items.push(format!("node={}", self.node));
+        }
+        if self.billing > 0 {
+            items.push(format!("billing={}", self.billing));
+        }
+        items.extend(self.other.iter().map(|(n, v)| format!("{}={}", n, v)));
+        items.extend(self.gres.iter().map(|(g, c)| format!("gres/{}={}", g, c)));
+        write!(f, "{}", items.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tres() {
+        let list = "cpu=8,mem=64G,node=1,billing=8,gres/gpu:a100=4,gres/gpu:v100=2";
+        let tres = Tres::parse(list);
+        assert_eq!(tres.cpu, 8);
+        assert_eq!(tres.mem, 64 << 30);
+        assert_eq!(tres.gpus(), 6);
+        assert_eq!(tres.gres("gpu:a100"), 4);
+        assert_eq!(tres.amount("gres/gpu"), 6.0);
+        assert_eq!(tres.amount("license/matlab"), 0.0);
+        assert_eq!(tres.to_string(), list);
+        assert_eq!(Tres::parse("gres/gpu=3,gres/gpu:a100=3").gpus(), 3);
+        assert_eq!(Tres::parse("mem=4000").mem, 4000 << 20);
+        assert_eq!(
+            Tres::parse("cpu=2,mem=1536M,license/matlab=1").to_string(),
+            "cpu=2,mem=1536M,license/matlab=1"
+        );
+
+        let sum: Tres = [tres.clone(), Tres::parse("cpu=2,mem=8G,gres/gpu:a100=1")]
+            .iter()
+            .sum();
+        assert_eq!(sum.summary(), "10 CPUs, 72G, 7 GPUs");
+        assert_eq!(
+            tres.table()[..3],
+            [
+                ("CPUs".to_owned(), "8".to_owned()),
+                ("Memory".to_owned(), "64G".to_owned()),
+                ("Nodes".to_owned(), "1".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam::{
+    channel::{Receiver, Sender, unbounded},
+    select,
+};
+
+use crate::app::AppMessage;
+use crate::slurm;
+
+/// Resource usage of a running job from `sstat`, summed over its steps.
+#[derive(Clone)]
+pub struct Sample {
+    pub at: Instant,
+    /// CPU time of all tasks.
+    pub cpu_time: Duration,
+    /// Average CPU time of a task, of the step where it is largest.
+    pub ave_cpu: Duration,
+    /// Largest resident set size of any task, in bytes.
+    pub max_rss: u64,
+    pub disk_read: u64,
+    pub disk_write: u64,
+    /// Average GPU utilization in percent, if the cluster gathers it.
+    pub gpu_util: Option<f64>,
+}
+
+struct UsageWatcher {
+    app: Sender<AppMessage>,
+    receiver: Receiver<UsageWatcherMessage>,
+    job_id: Option<String>,
+    interval: Duration,
+}
+
+enum UsageWatcherMessage {
+    JobId(Option<String>),
+}
+
+/// Polls `sstat` for a single job, to spare the Slurm controller.
+pub struct UsageWatcherHandle {
+    sender: Sender<UsageWatcherMessage>,
+    job_id: Option<String>,
+}
+
+const FIELDS: [&str; 5] = [
+    "MaxRSS",
+    "TRESUsageInTot",
+    "TRESUsageOutTot",
+    "TRESUsageInAve",
+    "AveCPU",
+];
+
+impl UsageWatcher {
+    fn new(
+        app: Sender<AppMessage>,
+        receiver: Receiver<UsageWatcherMessage>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            app,
+            receiver,
+            job_id: None,
+            interval,
+        }
+    }
+
+    fn run(&mut self) {
+        loop {
+            if let Some(job_id) = &self.job_id {
+                let sample = slurm::output(Command::new("sstat").args([
+                    "--jobs",
+                    job_id,
+                    "--allsteps",
+                    "--noheader",
+                    "--parsable2",
+                    "--format",
+                    &FIELDS.join(","),
+                ]))
+                .and_then(|output| {
+                    parse_sstat(&output).ok_or_else(|| "sstat reports no running steps".to_owned())
+                });
+                if self
+                    .app
+                    .send(AppMessage::Usage(job_id.clone(), sample))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+            select! {
+                recv(self.receiver) -> msg => match msg {
+                    Ok(UsageWatcherMessage::JobId(job_id)) => self.job_id = job_id,
+                    Err(_) => return,
+                },
+                default(self.interval) => {}
+            }
+        }
+    }
+}
+
+/// The value of a TRES in a list like `cpu=00:01:02,fs/disk=1234,mem=12M`.
+fn tres<'a>(list: &'a str, name: &str) -> Option<&'a str> {
+    list.split(',')
+        .filter_map(|t| t.split_once('='))
+        .find(|(n, _)| *n == name)
+        .map(|(_, v)| v)
+}
+
+/// Sums up the lines of the steps. Returns `None` if there are none.
+fn parse_sstat(output: &str) -> Option<Sample> {
+    let steps: Vec<Vec<&str>> = output
+        .lines()
+        .map(|l| l.split('|').collect::<Vec<_>>())
+        .filter(|r| r.len() == FIELDS.len())
+        .collect();
+    if steps.is_empty() {
+        return None;
+    }
+    let sum = |field: usize, name: &str, parse: fn(&str) -> Option<u64>| {
+        steps
+            .iter()
+            .filter_map(|s| tres(s[field], name).and_then(parse))
+            .sum::<u64>()
+    };
+    let gpu_util: Vec<f64> = steps
+        .iter()
+        .filter_map(|s| tres(s[3], "gres/gpuutil")?.parse().ok())
+        .collect();
+    Some(Sample {
+        at: Instant::now(),
+        cpu_time: steps
+            .iter()
+            .filter_map(|s| tres(s[1], "cpu").and_then(slurm::parse_duration))
+            .sum(),
+        ave_cpu: steps
+            .iter()
+            .filter_map(|s| slurm::parse_duration(s[4]))
+            .max()
+            .unwrap_or_default(),
+        max_rss: steps
+            .iter()
+            .filter_map(|s| slurm::parse_size(s[0]))
+            .max()
+            .unwrap_or(0),
+        disk_read: sum(1, "fs/disk", slurm::parse_size),
+        disk_write: sum(2, "fs/disk", slurm::parse_size),
+        gpu_util: (!gpu_util.is_empty())
+            .then(|| gpu_util.iter().sum::<f64>() / gpu_util.len() as f64),
+    })
+}
+
+impl UsageWatcherHandle {
+    pub fn new(app: Sender<AppMessage>, interval: Duration) -> Self {
+        let (sender, receiver) = unbounded();
+        let mut actor = UsageWatcher::new(app, receiver, interval);
+        thread::spawn(move || actor.run());
+
+        Self {
+            sender,
+            job_id: None,
+        }
+    }
+
+    pub fn set_job_id(&mut self, job_id: Option<String>) {
+        if self.job_id != job_id {
+            self.job_id = job_id.clone();
+            self.sender
+                .send(UsageWatcherMessage::JobId(job_id))
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sstat() {
+        let output = "\
+2048K|cpu=00:01:30,fs/disk=1048576,mem=2M|cpu=00:00:00,fs/disk=1024|cpu=00:01:30,mem=2M|00:01:30
+1G|cpu=01:00:00,fs/disk=0,mem=1G|cpu=00:00:00,fs/disk=2048|cpu=00:30:00,gres/gpuutil=40|00:30:00
+";
+        let sample = parse_sstat(output).unwrap();
+        assert_eq!(sample.cpu_time, Duration::from_secs(3690));
+        assert_eq!(sample.ave_cpu, Duration::from_secs(1800));
+        assert_eq!(sample.max_rss, 1 << 30);
+        assert_eq!(sample.disk_read, 1048576);
+        assert_eq!(sample.disk_write, 3072);
+        assert_eq!(sample.gpu_util, Some(40.0));
+        assert!(parse_sstat("").is_none());
+    }
+}